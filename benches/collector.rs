@@ -0,0 +1,81 @@
+//! Collected item updates of 50 collectors holding 200 items each.
+//!
+//! Only the distribution math is measured: the [`Collector`] methods are
+//! called directly, outside of the ECS, so the cost of the
+//! `update_collected_position` system itself (queries, change detection and
+//! item velocities) is not included. Every frame is measured as a worst case,
+//! where the rotation changed and the positions are computed again.
+//!
+//! Run with `cargo bench --bench collector`
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use garbage_project::{Collector, DistributionShape};
+
+const COLLECTORS: usize = 50;
+const ITEMS: usize = 200;
+const FRAMES: u32 = 1_000;
+const DT: f32 = 1.0 / 64.0;
+
+fn report(name: &str, elapsed: Duration, iterations: u32) {
+    println!("{name:<24} {:>12?} per iteration", elapsed / iterations);
+}
+
+fn entity(collector: usize, item: usize) -> Entity {
+    Entity::from_raw((collector * ITEMS + item) as u32)
+}
+
+fn direction(item: usize) -> Option<Dir2> {
+    Dir2::new(Vec2::from_angle(item as f32 * 2.4)).ok()
+}
+
+fn main() {
+    let mut collectors: Vec<_> = (0..COLLECTORS)
+        .map(|_| Collector::growing(4.0, 1.0, ITEMS))
+        .collect();
+
+    let start = Instant::now();
+    for (c, collector) in collectors.iter_mut().enumerate() {
+        for i in 0..ITEMS {
+            collector.insert(entity(c, i), 1.0, direction(i));
+        }
+    }
+    report("fill", start.elapsed(), 1);
+
+    for shape in [
+        DistributionShape::Circle,
+        DistributionShape::Arc,
+        DistributionShape::Line,
+        DistributionShape::Spiral,
+        DistributionShape::Rings,
+        DistributionShape::Sphere,
+    ] {
+        for collector in &mut collectors {
+            collector.set_shape(shape);
+        }
+        let start = Instant::now();
+        for _ in 0..FRAMES {
+            for collector in &mut collectors {
+                collector.update_rotation(Dir2::X, DT);
+                collector.update_positions(Vec3::ZERO, None);
+                black_box(collector.positions());
+            }
+        }
+        report(&format!("frame ({shape})"), start.elapsed(), FRAMES);
+
+        // An item leaves and another is collected in every collector each frame
+        let start = Instant::now();
+        for frame in 0..FRAMES as usize {
+            for (c, collector) in collectors.iter_mut().enumerate() {
+                let item = frame % ITEMS;
+                collector.remove(entity(c, item));
+                collector.insert(entity(c, item), 1.0, direction(frame));
+            }
+        }
+        report(&format!("churn ({shape})"), start.elapsed(), FRAMES);
+    }
+}
@@ -14,6 +14,7 @@ use bevy_mod_outline::{
 mod plugins;
 
 use plugins::*;
+pub use plugins::{Collector, DistributionShape, SaveBuild};
 
 const APP_NAME: &str = env!("CARGO_PKG_NAME");
 const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -157,9 +158,9 @@ impl Command for StartGame {
         // players
         reset_players(world);
         // enemies
-        spawn_enemies(self.worm_count, self.turret_count, world);
-        // items
-        spawn_builds(100, None, None)(world);
+        let enemies = spawn_enemies(self.worm_count, self.turret_count, world);
+        // items, enemies are not spawned yet so their positions are excluded explicitly
+        place_builds(world, 100, None, None, &enemies);
         // Setup current game
         world.insert_resource(self)
     }
@@ -2,13 +2,16 @@ use std::ops::Deref;
 
 use avian3d::prelude::*;
 use bevy::{
-    ecs::component::{ComponentHooks, StorageType},
+    ecs::{
+        component::{ComponentHooks, StorageType},
+        system::SystemParam,
+    },
     log,
     prelude::*,
 };
 use bevy_mod_outline::OutlineVolume;
 
-use super::player::Player;
+use super::{enemies::Enemy, garbage::Collected, player::Player};
 
 pub struct CommonPlugin;
 
@@ -16,11 +19,36 @@ impl Plugin for CommonPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Health>()
             .register_type::<Damage>()
+            .register_type::<DamageType>()
+            .register_type::<Resistances>()
+            .register_type::<Armor>()
+            .register_type::<ImpactDamage>()
+            .register_type::<DamageSource>()
+            .register_type::<Team>()
+            .register_type::<FriendlyFire>()
+            .init_resource::<FriendlyFire>()
+            .register_type::<ImpactDamageConfig>()
+            .init_resource::<ImpactDamageConfig>()
+            .add_event::<DamageDealt>()
             .register_type::<Dead>()
             .register_type::<Invincible>()
+            .register_type::<HitStun>()
+            .register_type::<KnockbackConfig>()
+            .init_resource::<KnockbackConfig>()
             .add_systems(First, despawn_deads)
             .add_systems(PreUpdate, handle_death)
-            .add_systems(Update, (direct_damage, tick_invincibility));
+            .add_systems(
+                PhysicsSchedule,
+                store_impact_velocities.in_set(PhysicsStepSet::First),
+            )
+            .add_systems(
+                Update,
+                (
+                    (direct_damage, apply_knockback).chain(),
+                    tick_invincibility,
+                    tick_hit_stun,
+                ),
+            );
     }
 }
 
@@ -37,11 +65,17 @@ impl Health {
     }
 
     /// Returns `true` if health is still over 0
-    pub fn damage(&mut self, amount: u16) -> bool {
+    pub const fn damage(&mut self, amount: u16) -> bool {
         self.current = self.current.saturating_sub(amount);
         self.current > 0
     }
 
+    /// Applies `amount` of damage, returning `true` if it killed the entity
+    pub const fn damage_killed(&mut self, amount: u16) -> bool {
+        let was_alive = self.current > 0;
+        !self.damage(amount) && was_alive
+    }
+
     pub fn heal(&mut self, amount: u16) {
         self.current += amount;
         self.current = self.current.min(self.max);
@@ -56,7 +90,7 @@ impl Health {
         self.current as f32 / self.max as f32
     }
 
-    pub fn reset(&mut self) {
+    pub const fn reset(&mut self) {
         self.current = self.max;
     }
 }
@@ -118,14 +152,295 @@ impl Default for Invincible {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum DamageType {
+    /// Impact of thrown items
+    Blunt,
+    /// Worm plunge attacks
+    Crush,
+    Explosive,
+    /// Touching an enemy
+    #[default]
+    Contact,
+}
+
+/// Prevents movement and skills while the timer, in seconds, is running
 #[derive(Debug, Component, Reflect)]
+#[component(storage = "SparseSet")]
 #[reflect(Component)]
-pub struct Damage(pub u16);
+pub struct HitStun(pub f32);
+
+/// Knockback and [`HitStun`] applied to dynamic bodies when taking damage
+#[derive(Debug, Clone, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct KnockbackConfig {
+    /// Speed given to a body of `reference_mass` per point of damage
+    pub speed_per_damage: f32,
+    pub max_speed: f32,
+    pub reference_mass: f32,
+    /// Exponent applied to the inverse mass ratio, heavier bodies are pushed
+    /// less
+    pub mass_exponent: f32,
+    /// Hit stun duration per point of damage
+    pub stun_per_damage: f32,
+    pub max_stun: f32,
+}
+
+impl Default for KnockbackConfig {
+    fn default() -> Self {
+        Self {
+            speed_per_damage: 2.0,
+            max_speed: 60.0,
+            reference_mass: 5.0,
+            mass_exponent: 0.5,
+            stun_per_damage: 0.02,
+            max_stun: 0.6,
+        }
+    }
+}
+
+impl KnockbackConfig {
+    /// Computes the knockback impulse magnitude of a hit of `damage` on a
+    /// body of `mass`
+    pub fn impulse(&self, damage: u16, mass: f32) -> f32 {
+        let speed = (f32::from(damage) * self.speed_per_damage).min(self.max_speed);
+        let mass_ratio = (self.reference_mass / mass.max(f32::EPSILON)).powf(self.mass_exponent);
+        speed * mass_ratio * mass
+    }
+
+    /// Computes the hit stun duration of a hit of `damage`
+    pub fn stun(&self, damage: u16) -> f32 {
+        (f32::from(damage) * self.stun_per_damage).min(self.max_stun)
+    }
+}
+
+#[derive(Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Damage {
+    pub amount: u16,
+    pub kind: DamageType,
+}
+
+impl Damage {
+    pub const fn new(amount: u16, kind: DamageType) -> Self {
+        Self { amount, kind }
+    }
+}
+
+/// Per [`DamageType`] multipliers of the damage taken by an entity
+#[derive(Debug, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct Resistances {
+    pub blunt: f32,
+    pub crush: f32,
+    pub explosive: f32,
+    pub contact: f32,
+}
+
+impl Default for Resistances {
+    fn default() -> Self {
+        Self {
+            blunt: 1.0,
+            crush: 1.0,
+            explosive: 1.0,
+            contact: 1.0,
+        }
+    }
+}
+
+impl Resistances {
+    pub const fn multiplier(&self, kind: DamageType) -> f32 {
+        match kind {
+            DamageType::Blunt => self.blunt,
+            DamageType::Crush => self.crush,
+            DamageType::Explosive => self.explosive,
+            DamageType::Contact => self.contact,
+        }
+    }
+}
+
+/// Flat reduction of every hit taken by an entity, applied after its
+/// [`Resistances`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Component, Reflect)]
+#[reflect(Component)]
+pub struct Armor(pub u16);
+
+/// Computes the damage actually taken by entities from their [`Resistances`]
+/// and [`Armor`]
+#[derive(SystemParam)]
+pub struct Defenses<'w, 's> {
+    pub defenses: Query<'w, 's, (Option<&'static Resistances>, Option<&'static Armor>)>,
+}
+
+impl<'w, 's> Defenses<'w, 's> {
+    /// Returns the damage taken by `target` from a hit of `amount` and `kind`
+    pub fn mitigate(&self, target: Entity, amount: u16, kind: DamageType) -> u16 {
+        let Ok((resistances, armor)) = self.defenses.get(target) else {
+            return amount;
+        };
+        let multiplier = resistances.map_or(1.0, |r| r.multiplier(kind));
+        let amount = (f32::from(amount) * multiplier).round() as u16;
+        amount.saturating_sub(armor.map_or(0, |a| a.0))
+    }
+}
+
+/// Scales the entity [`Damage`] by its impact speed and mass, following the
+/// [`ImpactDamageConfig`] curve
+#[derive(Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct ImpactDamage {
+    /// Linear velocity at the start of the last physics step, before contacts
+    /// were solved
+    pub velocity: Vec3,
+}
+
+/// Impact damage curve of [`ImpactDamage`] entities
+#[derive(Debug, Clone, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct ImpactDamageConfig {
+    /// Relative speed under which impacts deal no damage
+    pub min_speed: f32,
+    /// Relative speed at which an item of `reference_mass` deals its base
+    /// damage
+    pub reference_speed: f32,
+    pub reference_mass: f32,
+    /// Exponent applied to the speed ratio
+    pub speed_exponent: f32,
+    /// Exponent applied to the mass ratio
+    pub mass_exponent: f32,
+    /// Maximum multiplier of the base damage
+    pub max_multiplier: f32,
+}
+
+impl Default for ImpactDamageConfig {
+    fn default() -> Self {
+        Self {
+            min_speed: 4.0,
+            reference_speed: 50.0,
+            reference_mass: 1.0,
+            speed_exponent: 1.0,
+            mass_exponent: 0.5,
+            max_multiplier: 4.0,
+        }
+    }
+}
+
+impl ImpactDamageConfig {
+    /// Computes the damage dealt by an impact at `speed` of an item of
+    /// `mass`, with a `base` damage
+    pub fn damage(&self, base: u16, speed: f32, mass: f32) -> u16 {
+        if speed < self.min_speed {
+            return 0;
+        }
+        let speed_ratio = (speed / self.reference_speed).powf(self.speed_exponent);
+        let mass_ratio = (mass / self.reference_mass).powf(self.mass_exponent);
+        let multiplier = (speed_ratio * mass_ratio).min(self.max_multiplier);
+        (f32::from(base) * multiplier).round() as u16
+    }
+}
+
+/// Attributes the [`Damage`] of an entity, like a thrown item, to the
+/// collector it comes from and the owner of that collector
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+#[reflect(Component)]
+pub struct DamageSource {
+    pub collector: Entity,
+    /// Player or enemy owning `collector`
+    pub instigator: Entity,
+}
+
+/// Allegiance of players and enemies, damage between entities of the same
+/// team follows the [`FriendlyFire`] policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component, Reflect)]
+#[reflect(Component)]
+pub struct Team(pub u8);
+
+impl Team {
+    pub const PLAYERS: Self = Self(0);
+    pub const ENEMIES: Self = Self(1);
+}
+
+/// Damage policy between entities of the same [`Team`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Resource, Reflect)]
+#[reflect(Resource)]
+pub enum FriendlyFire {
+    /// Teammates cannot hurt each other and their throws pass through them
+    #[default]
+    Off,
+    /// Damage between teammates is multiplied by the given ratio
+    Reduced(f32),
+    Full,
+}
+
+impl FriendlyFire {
+    /// Returns `true` if `a` and `b` are teammates
+    pub fn allies(a: Option<&Team>, b: Option<&Team>) -> bool {
+        a.zip(b).is_some_and(|(a, b)| a == b)
+    }
+
+    /// Returns the `damage` dealt by an entity of team `from` to one of team `to`
+    pub fn apply(self, damage: u16, from: Option<&Team>, to: Option<&Team>) -> u16 {
+        if !Self::allies(from, to) {
+            return damage;
+        }
+        match self {
+            Self::Off => 0,
+            Self::Reduced(ratio) => (f32::from(damage) * ratio).round() as u16,
+            Self::Full => damage,
+        }
+    }
+}
+
+/// Resolves the [`Team`] entities fight for. Collectors belong to their
+/// parent and collected items to the owner of their collector
+#[derive(SystemParam)]
+pub struct Teams<'w, 's> {
+    pub teams: Query<'w, 's, &'static Team>,
+    pub collected: Query<'w, 's, &'static Collected>,
+    pub parents: Query<'w, 's, &'static Parent>,
+}
+
+impl<'w, 's> Teams<'w, 's> {
+    /// Returns the owner of `collector`, its parent if it has one
+    pub fn collector_owner(&self, collector: Entity) -> Entity {
+        self.parents.get(collector).map_or(collector, Parent::get)
+    }
+
+    /// Returns the entity `entity` fights for, the owner of its collector for
+    /// collected items and itself otherwise
+    pub fn owner(&self, entity: Entity) -> Entity {
+        self.collected
+            .get(entity)
+            .map_or(entity, |c| self.collector_owner(c.collector_entity))
+    }
+
+    pub fn team(&self, entity: Entity) -> Option<&Team> {
+        self.teams.get(self.owner(entity)).ok()
+    }
+
+    pub fn collector_team(&self, collector: Entity) -> Option<&Team> {
+        self.teams.get(self.collector_owner(collector)).ok()
+    }
+}
 
 #[derive(Debug, Component, Reflect)]
 #[reflect(Component)]
 pub struct Dead;
 
+/// Sent when the [`Health`] of `target` is reduced by a hit
+#[derive(Debug, Clone, Copy, Event)]
+pub struct DamageDealt {
+    /// Entity dealing the damage, like a thrown item or an enemy
+    pub source: Entity,
+    /// Entity responsible for the damage, the owner of the collector `source`
+    /// was thrown from or `source` itself
+    pub instigator: Entity,
+    pub target: Entity,
+    pub amount: u16,
+    /// Whether this hit brought the `target` health to 0
+    pub killed: bool,
+}
+
 fn tick_invincibility(
     mut commands: Commands,
     time: Res<Time>,
@@ -139,29 +454,78 @@ fn tick_invincibility(
     }
 }
 
+fn store_impact_velocities(mut entities: Query<(&mut ImpactDamage, &LinearVelocity)>) {
+    for (mut impact, linvel) in &mut entities {
+        impact.velocity = linvel.0;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn direct_damage(
     mut commands: Commands,
+    config: Res<ImpactDamageConfig>,
+    friendly_fire: Res<FriendlyFire>,
     mut events: EventReader<CollisionStarted>,
+    mut damage_evw: EventWriter<DamageDealt>,
     mut entities: Query<
         (
             Option<&Damage>,
             Option<&mut Health>,
             Has<Invincible>,
             Has<Player>,
+            Option<&LinearVelocity>,
+            Option<(&Mass, &ImpactDamage)>,
+            Option<&DamageSource>,
         ),
         Or<(With<Health>, With<Damage>)>,
     >,
+    teams: Teams,
+    defenses: Defenses,
 ) {
     for CollisionStarted(a, b) in events.read() {
         let Ok(
-            [(damage_a, health_a, invicible_a, is_player_a), (damage_b, health_b, invicible_b, is_player_b)],
+            [(damage_a, health_a, invicible_a, is_player_a, linvel_a, impact_a, source_a), (damage_b, health_b, invicible_b, is_player_b, linvel_b, impact_b, source_b)],
         ) = entities.get_many_mut([*a, *b])
         else {
             continue;
         };
+        // The solver already resolved the impact, impact damage entities use
+        // their velocity from before the step
+        let velocity = |linvel: Option<&LinearVelocity>, impact: Option<(&Mass, &ImpactDamage)>| {
+            impact.map_or_else(|| linvel.map_or(Vec3::ZERO, |v| v.0), |(_, i)| i.velocity)
+        };
+        let speed = velocity(linvel_a, impact_a).distance(velocity(linvel_b, impact_b));
+        let amount = |damage: &Damage, impact: Option<(&Mass, &ImpactDamage)>| {
+            impact.map_or(damage.amount, |(mass, _)| {
+                config.damage(damage.amount, speed, mass.0)
+            })
+        };
+        let instigator_a = source_a.map_or(*a, |s| s.instigator);
+        let instigator_b = source_b.map_or(*b, |s| s.instigator);
+        let team_a = teams.team(instigator_a);
+        let team_b = teams.team(instigator_b);
+        let damage_a = damage_a
+            .map(|d| {
+                let amount = friendly_fire.apply(amount(d, impact_a), team_a, teams.team(*b));
+                defenses.mitigate(*b, amount, d.kind)
+            })
+            .filter(|d| *d > 0);
+        let damage_b = damage_b
+            .map(|d| {
+                let amount = friendly_fire.apply(amount(d, impact_b), team_b, teams.team(*a));
+                defenses.mitigate(*a, amount, d.kind)
+            })
+            .filter(|d| *d > 0);
         if !invicible_b {
             if let Some((damage, mut health)) = damage_a.zip(health_b) {
-                health.damage(damage.0);
+                let killed = health.damage_killed(damage);
+                damage_evw.send(DamageDealt {
+                    source: *a,
+                    instigator: instigator_a,
+                    target: *b,
+                    amount: damage,
+                    killed,
+                });
                 commands.entity(*b).insert(if is_player_b {
                     Invincible::player()
                 } else {
@@ -171,7 +535,14 @@ fn direct_damage(
         }
         if !invicible_a {
             if let Some((damage, mut health)) = damage_b.zip(health_a) {
-                health.damage(damage.0);
+                let killed = health.damage_killed(damage);
+                damage_evw.send(DamageDealt {
+                    source: *b,
+                    instigator: instigator_b,
+                    target: *a,
+                    amount: damage,
+                    killed,
+                });
                 commands.entity(*a).insert(if is_player_a {
                     Invincible::player()
                 } else {
@@ -182,6 +553,70 @@ fn direct_damage(
     }
 }
 
+/// Adds `impulse` to the [`ExternalImpulse`] of `entity`, accumulating with
+/// the impulses already applied this frame instead of replacing them
+pub fn add_impulse(entity: Entity, impulse: Vec3) -> impl FnOnce(&mut World) {
+    move |world| {
+        let Some(mut entity) = world.get_entity_mut(entity) else {
+            return;
+        };
+        if let Some(mut external) = entity.get_mut::<ExternalImpulse>() {
+            external.apply_impulse(impulse);
+        } else {
+            entity.insert(ExternalImpulse::new(impulse));
+        }
+    }
+}
+
+fn apply_knockback(
+    mut commands: Commands,
+    config: Res<KnockbackConfig>,
+    mut events: EventReader<DamageDealt>,
+    positions: Query<&GlobalTransform>,
+    targets: Query<
+        (&RigidBody, &Mass),
+        (Or<(With<Player>, With<Enemy>)>, With<Health>, Without<Dead>),
+    >,
+) {
+    for event in events.read() {
+        let Ok((body, mass)) = targets.get(event.target) else {
+            continue;
+        };
+        if !body.is_dynamic() {
+            continue;
+        }
+        let Ok([source, target]) = positions.get_many([event.source, event.target]) else {
+            continue;
+        };
+        // Knockback is horizontal, away from the source
+        let delta = target.translation() - source.translation();
+        let Some(direction) = Vec3::new(delta.x, 0.0, delta.z).try_normalize() else {
+            continue;
+        };
+        commands.add(add_impulse(
+            event.target,
+            direction * config.impulse(event.amount, mass.0),
+        ));
+        commands
+            .entity(event.target)
+            .insert(HitStun(config.stun(event.amount)));
+    }
+}
+
+fn tick_hit_stun(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut stunned: Query<(Entity, &mut HitStun)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut stun) in &mut stunned {
+        stun.0 -= dt;
+        if stun.0 <= 0.0 {
+            commands.entity(entity).remove::<HitStun>();
+        }
+    }
+}
+
 fn handle_death(mut commands: Commands, entities: Query<(Entity, &Health), Changed<Health>>) {
     for (entity, health) in &entities {
         if health.current > 0 {
@@ -200,3 +635,95 @@ fn despawn_deads(mut commands: Commands, entities: Query<(Entity, Option<&Player
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+
+    #[test]
+    fn friendly_fire_policies() {
+        let (players, enemies) = (Some(&Team::PLAYERS), Some(&Team::ENEMIES));
+        for policy in [
+            FriendlyFire::Off,
+            FriendlyFire::Reduced(0.5),
+            FriendlyFire::Full,
+        ] {
+            assert_eq!(policy.apply(10, players, enemies), 10);
+            assert_eq!(policy.apply(10, players, None), 10);
+            assert_eq!(policy.apply(10, None, None), 10);
+        }
+        assert_eq!(FriendlyFire::Off.apply(10, players, players), 0);
+        assert_eq!(FriendlyFire::Reduced(0.25).apply(10, players, players), 3);
+        assert_eq!(FriendlyFire::Full.apply(10, enemies, enemies), 10);
+    }
+
+    #[test]
+    fn collected_items_fight_for_their_collector_owner() {
+        let mut world = World::new();
+        let player = world.spawn(Team::PLAYERS).id();
+        let collector = world
+            .spawn(GlobalTransform::default())
+            .set_parent(player)
+            .id();
+        let orphan = world.spawn(GlobalTransform::default()).id();
+        let item = world
+            .spawn((
+                GlobalTransform::default(),
+                Collected {
+                    collector_entity: collector,
+                },
+            ))
+            .id();
+        let loose = world.spawn(GlobalTransform::default()).id();
+
+        let mut state = SystemState::<Teams>::new(&mut world);
+        let teams = state.get(&world);
+        assert_eq!(teams.owner(item), player);
+        assert_eq!(teams.team(item), Some(&Team::PLAYERS));
+        assert_eq!(teams.collector_team(collector), Some(&Team::PLAYERS));
+        assert_eq!(teams.collector_owner(orphan), orphan);
+        assert_eq!(teams.team(loose), None);
+    }
+
+    #[test]
+    fn impact_damage_curve() {
+        let config = ImpactDamageConfig::default();
+        // Too slow
+        assert_eq!(config.damage(10, 3.9, 1.0), 0);
+        // Reference speed and mass
+        assert_eq!(config.damage(10, 50.0, 1.0), 10);
+        // Linear in speed, square root of mass
+        assert_eq!(config.damage(10, 25.0, 1.0), 5);
+        assert_eq!(config.damage(10, 50.0, 4.0), 20);
+        // Capped
+        assert_eq!(config.damage(10, 500.0, 100.0), 40);
+    }
+
+    #[test]
+    fn defenses_apply_resistances_then_armor() {
+        let mut world = World::new();
+        let bare = world.spawn_empty().id();
+        let armored = world.spawn(Armor(3)).id();
+        let resistant = world
+            .spawn((
+                Resistances {
+                    blunt: 0.5,
+                    explosive: 2.0,
+                    ..default()
+                },
+                Armor(2),
+            ))
+            .id();
+
+        let mut state = SystemState::<Defenses>::new(&mut world);
+        let defenses = state.get(&world);
+        assert_eq!(defenses.mitigate(bare, 10, DamageType::Blunt), 10);
+        assert_eq!(defenses.mitigate(armored, 10, DamageType::Crush), 7);
+        assert_eq!(defenses.mitigate(armored, 2, DamageType::Crush), 0);
+        assert_eq!(defenses.mitigate(resistant, 9, DamageType::Blunt), 3);
+        assert_eq!(defenses.mitigate(resistant, 10, DamageType::Explosive), 18);
+        assert_eq!(defenses.mitigate(resistant, 10, DamageType::Contact), 8);
+    }
+}
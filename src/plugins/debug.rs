@@ -3,15 +3,14 @@ use bevy_egui::{
     egui::{self, Widget},
     EguiContexts,
 };
-use strum::IntoEnumIterator;
 
 use crate::{clear_all, Health, StartGame};
 
 use super::{
     enemies::{SpawnTurret, SpawnWorm},
     garbage::{
-        spawn_builds, spawn_some_garbage, AvailableItemBuilds, GarbageAssets, GarbageBundle,
-        GarbageItem, SpawnBuild,
+        spawn_builds, spawn_some_garbage, AvailableItemBuilds, Collected, Collector, GarbageAssets,
+        GarbageBundle, GarbageItem, SaveBuild, SpawnBuild,
     },
     player::{ActiveSkill, GameController, GamepadCategory, Player, PlayerConnected, SkillState},
 };
@@ -40,18 +39,25 @@ fn debug_ui(mut context: EguiContexts, mut ui_opts: ResMut<UiDebugOptions>) {
 fn commands_ui(
     mut commands: Commands,
     mut context: EguiContexts,
-    assets: Res<GarbageAssets>,
+    assets: Option<Res<GarbageAssets>>,
     builds: Res<AvailableItemBuilds>,
     mut pos: Local<Vec2>,
     mut rot: Local<f32>,
     mut worm_size: Local<usize>,
     mut start_game: Local<StartGame>,
+    mut export: Local<(String, f32)>,
     mut worm_evw: EventWriter<SpawnWorm>,
     mut turret_evw: EventWriter<SpawnTurret>,
+    items: Query<(Entity, &GlobalTransform), (With<GarbageItem>, Without<Collected>)>,
+    collectors: Query<(Entity, &Collector, &GlobalTransform, Option<&Parent>)>,
+    names: Query<&Name>,
 ) {
     if *worm_size == 0 {
         *worm_size = 5;
     }
+    if export.0.is_empty() {
+        *export = ("custom".to_owned(), 5.0);
+    }
     let ctx = context.ctx_mut();
     egui::Window::new("Commands").show(ctx, |ui| {
         if ui.button("Clear Map").clicked() {
@@ -70,15 +76,20 @@ fn commands_ui(
             commands.add(*start_game);
         }
         ui.heading("Garbage");
-        egui::ComboBox::from_id_source("Spawn Garbage Item")
-            .selected_text("Spawn Garbage")
-            .show_ui(ui, |ui| {
-                for item in GarbageItem::iter() {
-                    if ui.button(format!("{item:?}")).clicked() {
-                        commands.spawn(GarbageBundle::new(item, &assets));
+        // The item catalog may still be loading
+        if let Some(assets) = &assets {
+            egui::ComboBox::from_id_source("Spawn Garbage Item")
+                .selected_text("Spawn Garbage")
+                .show_ui(ui, |ui| {
+                    for item in assets.catalog.iter() {
+                        if ui.button(assets.catalog[item].name.as_str()).clicked() {
+                            commands.spawn(GarbageBundle::new(item, assets));
+                        }
                     }
-                }
-            });
+                });
+        } else {
+            ui.label("Loading item catalog...");
+        }
         if ui.button("Spawn 50 garbage items").clicked() {
             commands.add(spawn_some_garbage(50, None, None));
         }
@@ -111,6 +122,55 @@ fn commands_ui(
             commands.add(spawn_builds(50, None, None));
         }
 
+        ui.heading("Export");
+        // Saving builds needs the item catalog
+        ui.add_enabled_ui(assets.is_some(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Name");
+                ui.text_edit_singleline(&mut export.0);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Half extent");
+                egui::Slider::new(&mut export.1, 1.0..=20.0).ui(ui);
+            });
+            let origin =
+                Transform::from_xyz(pos.x, 1.0, pos.y).with_rotation(Quat::from_rotation_y(*rot));
+            if ui.button("Save items around position").clicked() {
+                let entities = items
+                    .iter()
+                    .filter(|(_, gtr)| {
+                        let delta = gtr.translation() - origin.translation;
+                        delta.x.abs() <= export.1 && delta.z.abs() <= export.1
+                    })
+                    .map(|(entity, _)| entity)
+                    .collect();
+                commands.add(SaveBuild {
+                    entities,
+                    origin,
+                    name: export.0.clone(),
+                });
+            }
+            egui::ComboBox::from_label("Save collected items")
+                .selected_text("Collector")
+                .show_ui(ui, |ui| {
+                    for (entity, collector, gtr, parent) in &collectors {
+                        let label = parent
+                            .and_then(|p| names.get(p.get()).ok())
+                            .map_or_else(|| format!("{entity}"), ToString::to_string);
+                        if ui
+                            .button(format!("{label} ({})", collector.len()))
+                            .clicked()
+                        {
+                            commands.add(SaveBuild {
+                                entities: collector.collected().to_vec(),
+                                origin: gtr.compute_transform(),
+                                name: export.0.clone(),
+                            });
+                        }
+                    }
+                });
+        });
+
         ui.heading("Enemies");
         ui.horizontal(|ui| {
             ui.label("Worm Size");
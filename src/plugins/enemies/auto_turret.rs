@@ -1,12 +1,14 @@
 use super::{
-    assets::EnemyAssets, Enemy, PlayerDetectorBundle, SpawnTurret, TargetPlayer, ENEMY_COLOR,
+    assets::EnemyAssets, Enemy, ItemArmor, PlayerDetectorBundle, SpawnTurret, TargetPlayer,
+    ENEMY_COLOR,
 };
 use crate::{
     plugins::{
         garbage::{Collector, CollectorBundle, CollectorParticlesBundle},
         particles::DeathEffect,
     },
-    Damage, GameState, Health, ObjectLayer, ParticleConfig,
+    Armor, Damage, DamageType, GameState, Health, HitStun, ObjectLayer, ParticleConfig,
+    Resistances, Team,
 };
 use avian3d::prelude::*;
 use bevy::prelude::*;
@@ -38,6 +40,7 @@ impl Plugin for AutoTurretPlugin {
 pub struct AutoTurretBundle {
     pub pbr: PbrBundle,
     pub enemy: Enemy,
+    pub team: Team,
     pub state: TurretState,
     pub rigidbody: RigidBody,
     pub collider: Collider,
@@ -47,6 +50,9 @@ pub struct AutoTurretBundle {
     pub ang_damping: AngularDamping,
     pub health: Health,
     pub damage: Damage,
+    pub resistances: Resistances,
+    pub armor: Armor,
+    pub item_armor: ItemArmor,
     pub name: Name,
     pub death: DeathEffect,
     pub outline: OutlineBundle,
@@ -62,6 +68,7 @@ impl AutoTurretBundle {
                 ..default()
             },
             enemy: Enemy,
+            team: Team::ENEMIES,
             rigidbody: RigidBody::Dynamic,
             collider: assets.collider.clone(),
             layers: CollisionLayers::new(ObjectLayer::Enemy, LayerMask::ALL),
@@ -69,7 +76,13 @@ impl AutoTurretBundle {
             lin_damping: LinearDamping(1.0),
             ang_damping: AngularDamping(1.5),
             health: Health::new(BASE_HEALTH),
-            damage: Damage(BASE_DAMAGE),
+            damage: Damage::new(BASE_DAMAGE, DamageType::Contact),
+            resistances: Resistances {
+                explosive: 1.25,
+                ..default()
+            },
+            armor: Armor::default(),
+            item_armor: ItemArmor::new(1, 0.5, 5),
             name: Name::new("Auto Turret"),
             state: TurretState::default(),
             death: DeathEffect {
@@ -98,7 +111,7 @@ pub enum TurretState {
 
 fn behave(
     mut commands: Commands,
-    mut enemies: Query<(Entity, &LinearVelocity, &mut TurretState, &Children)>,
+    mut enemies: Query<(Entity, &LinearVelocity, &mut TurretState, &Children), Without<HitStun>>,
     collectors: Query<&Collector>,
 ) {
     for (entity, linvel, mut state, children) in &mut enemies {
@@ -156,7 +169,9 @@ fn spawn_turret(
             ))
             .id();
         let mut collector_bundle =
-            CollectorBundle::growing(5.0, 3.0, ENEMY_COLOR, 6, ObjectLayer::Enemy);
+            CollectorBundle::growing(5.0, 3.0, ENEMY_COLOR, 6, ObjectLayer::Enemy)
+                .with_attraction(8.0, 30.0)
+                .with_damage_drop(0.5, 20.0, 2.0);
         collector_bundle.config.enabled = true;
         let collector = commands.spawn(collector_bundle).set_parent(enemy).id();
         commands
@@ -1,4 +1,4 @@
-use super::{map::MAP_SIZE, player::Player, spawn_some_garbage, Dead};
+use super::{garbage::Collector, map::MAP_SIZE, player::Player, spawn_some_garbage, Armor, Dead};
 use crate::ObjectLayer;
 use avian3d::prelude::*;
 use bevy::prelude::*;
@@ -22,9 +22,11 @@ impl Plugin for EnemiesPlugin {
         app.add_plugins((WormPlugin, AutoTurretPlugin, EnemyAssetsPlugin))
             .register_type::<Enemy>()
             .register_type::<TargetPlayer>()
+            .register_type::<ItemArmor>()
             .add_event::<SpawnTurret>()
             .add_event::<SpawnWorm>()
-            .add_systems(FixedUpdate, detect_players);
+            .add_systems(FixedUpdate, detect_players)
+            .add_systems(Update, update_item_armor);
     }
 }
 
@@ -50,6 +52,31 @@ impl PlayerDetector {
 #[derive(Component, Reflect, Deref)]
 pub struct TargetPlayer(Vec3);
 
+/// Sets the enemy [`Armor`] from the amount of items held by its collectors
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+#[reflect(Component)]
+pub struct ItemArmor {
+    pub base: u16,
+    /// Armor gained for each collected item
+    pub per_item: f32,
+    pub max: u16,
+}
+
+impl ItemArmor {
+    pub const fn new(base: u16, per_item: f32, max: u16) -> Self {
+        Self {
+            base,
+            per_item,
+            max,
+        }
+    }
+
+    pub fn armor(&self, items: usize) -> Armor {
+        let bonus = (items as f32 * self.per_item).round() as u16;
+        Armor(self.base.saturating_add(bonus).min(self.max))
+    }
+}
+
 #[derive(Bundle)]
 pub struct PlayerDetectorBundle {
     pub spatial: SpatialBundle,
@@ -106,6 +133,16 @@ fn detect_players(
     }
 }
 
+fn update_item_armor(
+    mut enemies: Query<(&ItemArmor, &mut Armor, &Children)>,
+    collectors: Query<&Collector>,
+) {
+    for (item_armor, mut armor, children) in &mut enemies {
+        let items = collectors.iter_many(children).map(Collector::len).sum();
+        armor.set_if_neq(item_armor.armor(items));
+    }
+}
+
 #[derive(Event, Reflect)]
 pub struct SpawnWorm {
     pub size: usize,
@@ -117,9 +154,12 @@ pub struct SpawnTurret {
     pub position: Vec2,
 }
 
-pub fn spawn_enemies(worms: usize, turrets: usize, world: &mut World) {
+/// Sends spawn events for the given amount of enemies and returns their
+/// positions
+pub fn spawn_enemies(worms: usize, turrets: usize, world: &mut World) -> Vec<Vec2> {
     let square = Rectangle::new(MAP_SIZE.x - 20.0, MAP_SIZE.y - 20.0);
     let mut rng = thread_rng();
+    let mut positions = Vec::with_capacity(worms + turrets);
     for i in 0..worms {
         let position = square.sample_interior(&mut rng);
         world.send_event(SpawnWorm {
@@ -127,9 +167,12 @@ pub fn spawn_enemies(worms: usize, turrets: usize, world: &mut World) {
             position,
         });
         spawn_some_garbage((12 + i) * 4, Some(Vec2::new(10.0, 10.0)), Some(position))(world);
+        positions.push(position);
     }
     for _ in 0..turrets {
         let position = square.sample_interior(&mut rng);
         world.send_event(SpawnTurret { position });
+        positions.push(position);
     }
+    positions
 }
@@ -1,17 +1,23 @@
+use std::f32::consts::FRAC_PI_4;
+
 use avian3d::prelude::*;
 use bevy::prelude::*;
 use bevy_mod_outline::{OutlineBundle, OutlineVolume};
 
 use crate::{
     plugins::{
-        garbage::{CollectorBundle, CollectorConfig, CollectorParticlesBundle, GarbageBody},
+        garbage::{
+            Chain, CollectorBundle, CollectorConfig, CollectorParticlesBundle, GarbageBody,
+            LimbParent,
+        },
         particles::DeathEffect,
     },
-    Damage, GameState, Health, ObjectLayer, ParticleConfig,
+    Armor, Damage, DamageType, GameState, Health, ObjectLayer, ParticleConfig, Resistances, Team,
 };
 
 use super::{
-    assets::EnemyAssets, Enemy, PlayerDetectorBundle, SpawnWorm, TargetPlayer, ENEMY_COLOR,
+    assets::EnemyAssets, Enemy, ItemArmor, PlayerDetectorBundle, SpawnWorm, TargetPlayer,
+    ENEMY_COLOR,
 };
 
 const PLUNGE_HEIGHT: f32 = 25.0;
@@ -19,6 +25,7 @@ const MAX_DISTANCE: f32 = 70.0;
 
 const BASE_HEALTH: u16 = 100;
 const BASE_DAMAGE: u16 = 20;
+const FIN_LENGTH: usize = 2;
 
 pub struct WormPlugin;
 
@@ -39,6 +46,7 @@ impl Plugin for WormPlugin {
 pub struct WormBundle {
     pub pbr: PbrBundle,
     pub enemy: Enemy,
+    pub team: Team,
     pub movement: WormMovement,
     pub state: WormState,
     pub rigidbody: RigidBody,
@@ -47,6 +55,9 @@ pub struct WormBundle {
     pub scale: GravityScale,
     pub health: Health,
     pub damage: Damage,
+    pub resistances: Resistances,
+    pub armor: Armor,
+    pub item_armor: ItemArmor,
     pub name: Name,
     pub death: DeathEffect,
     pub outline: OutlineBundle,
@@ -62,13 +73,21 @@ impl WormBundle {
                 ..default()
             },
             enemy: Enemy,
+            team: Team::ENEMIES,
             movement: WormMovement::new((size as f32 * 1.5).max(10.0), pos),
             rigidbody: RigidBody::Kinematic,
             scale: GravityScale(0.0),
             collider: assets.collider.clone(),
             layers: CollisionLayers::new(ObjectLayer::Enemy, LayerMask::ALL),
             health: Health::new(BASE_HEALTH),
-            damage: Damage(BASE_DAMAGE),
+            damage: Damage::new(BASE_DAMAGE, DamageType::Contact),
+            resistances: Resistances {
+                blunt: 0.8,
+                explosive: 1.5,
+                ..default()
+            },
+            armor: Armor::default(),
+            item_armor: ItemArmor::new(0, 0.25, 10),
             name: Name::new("Worm"),
             state: WormState::default(),
             death: DeathEffect {
@@ -192,10 +211,16 @@ fn detect_players(
 }
 
 fn handle_state_change(
-    enemies: Query<(&WormState, &Children), Changed<WormState>>,
+    mut enemies: Query<(&WormState, &Children, &mut Damage), Changed<WormState>>,
     mut collectors: Query<&mut CollectorConfig>,
 ) {
-    for (state, children) in &enemies {
+    for (state, children, mut damage) in &mut enemies {
+        // Plunging worms crush what they land on
+        damage.kind = if matches!(state, WormState::PlungeAttack(_)) {
+            DamageType::Crush
+        } else {
+            DamageType::Contact
+        };
         let mut configs = collectors.iter_many_mut(children);
         while let Some(mut config) = configs.fetch_next() {
             match state {
@@ -220,14 +245,14 @@ fn spawn_worm(
                 event.size,
             ))
             .id();
+        let body = worm_body(event.size);
+        let capacity = body.dorsal.len() + body.limbs.iter().map(|l| l.chain.len()).sum::<usize>();
         let mut collector_bundle =
-            CollectorBundle::fixed(5.0, 1.4, ENEMY_COLOR, event.size * 4, 4, ObjectLayer::Enemy);
+            CollectorBundle::fixed(5.0, 1.4, ENEMY_COLOR, capacity * 4, 4, ObjectLayer::Enemy)
+                .with_attraction(4.0, 20.0);
         collector_bundle.config.enabled = true;
         let collector = commands
-            .spawn((
-                collector_bundle,
-                GarbageBody::new(event.size, Vec3::ZERO, 2.5, -1.0),
-            ))
+            .spawn((collector_bundle, body))
             .set_parent(enemy)
             .id();
         commands
@@ -240,3 +265,24 @@ fn spawn_worm(
         ));
     }
 }
+
+/// Worm body with a pair of side fins behind the head
+fn worm_body(size: usize) -> GarbageBody {
+    let body = GarbageBody::new(size, Vec3::ZERO, 2.5, -1.0).with_terrain(0.5, Some(1.0));
+    if size < 2 {
+        return body;
+    }
+    let fin = || Chain::new(FIN_LENGTH, Vec3::ZERO, 2.0, FRAC_PI_4, 1.0);
+    body.with_limb(
+        LimbParent::Dorsal,
+        1,
+        Dir3::new(Vec3::new(1.0, 0.0, 0.5)).unwrap(),
+        fin(),
+    )
+    .with_limb(
+        LimbParent::Dorsal,
+        1,
+        Dir3::new(Vec3::new(-1.0, 0.0, 0.5)).unwrap(),
+        fin(),
+    )
+}
@@ -0,0 +1,279 @@
+use avian3d::prelude::*;
+use bevy::{
+    ecs::{
+        component::{ComponentHooks, StorageType},
+        system::EntityCommands,
+    },
+    prelude::*,
+    utils::HashSet,
+};
+use serde::Deserialize;
+
+use crate::{add_impulse, DamageDealt, DamageType, Dead, Defenses, FriendlyFire, Health, Teams};
+
+use super::{Collected, ThrownItem};
+
+pub struct BehaviorsPlugin;
+
+impl Plugin for BehaviorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Explosive>()
+            .register_type::<Sticky>()
+            .register_type::<Stuck>()
+            .register_type::<Bouncy>()
+            .add_systems(
+                Update,
+                (
+                    explode_thrown_items,
+                    stick_thrown_items,
+                    bounce_thrown_items,
+                    update_stuck_items,
+                ),
+            );
+    }
+}
+
+/// Special behaviour of a garbage item kind, defined in the catalog
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum ItemBehavior {
+    /// Explodes when hitting something once thrown, damaging and pushing
+    /// everything in `radius`
+    Explosive {
+        radius: f32,
+        damage: u16,
+        impulse: f32,
+    },
+    /// Attaches to what it hits once thrown, for `duration` seconds
+    Sticky { duration: f32 },
+    /// Keeps dealing damage for up to `bounces` rebounds once thrown
+    Bouncy { restitution: f32, bounces: u8 },
+}
+
+impl ItemBehavior {
+    /// Inserts the behaviour components on the garbage item `entity`
+    pub fn insert(self, entity: &mut EntityCommands) {
+        match self {
+            Self::Explosive {
+                radius,
+                damage,
+                impulse,
+            } => {
+                entity.insert(Explosive {
+                    radius,
+                    damage,
+                    impulse,
+                });
+            }
+            Self::Sticky { duration } => {
+                entity.insert(Sticky { duration });
+            }
+            Self::Bouncy {
+                restitution,
+                bounces,
+            } => {
+                entity.insert((
+                    Bouncy {
+                        bounces,
+                        remaining: bounces,
+                    },
+                    Restitution::new(restitution).with_combine_rule(CoefficientCombine::Max),
+                ));
+            }
+        }
+    }
+}
+
+#[derive(Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Explosive {
+    pub radius: f32,
+    pub damage: u16,
+    pub impulse: f32,
+}
+
+#[derive(Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Sticky {
+    pub duration: f32,
+}
+
+#[derive(Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Bouncy {
+    pub bounces: u8,
+    remaining: u8,
+}
+
+/// A [`Sticky`] item attached to `target`
+#[derive(Debug, Reflect)]
+#[reflect(Component)]
+pub struct Stuck {
+    pub target: Entity,
+    /// Transform relative to the target
+    offset: Transform,
+    timer: Timer,
+}
+
+impl Component for Stuck {
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks
+            .on_add(|mut world, entity, _| {
+                if let Some(mut body) = world.get_mut::<RigidBody>(entity) {
+                    *body = RigidBody::Kinematic;
+                }
+                if let Some(mut linvel) = world.get_mut::<LinearVelocity>(entity) {
+                    linvel.0 = Vec3::ZERO;
+                }
+            })
+            .on_remove(|mut world, entity, _| {
+                if let Some(mut body) = world.get_mut::<RigidBody>(entity) {
+                    *body = RigidBody::Dynamic;
+                }
+            });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn explode_thrown_items(
+    mut commands: Commands,
+    mut events: EventReader<CollisionStarted>,
+    mut damage_evw: EventWriter<DamageDealt>,
+    friendly_fire: Res<FriendlyFire>,
+    explosives: Query<(&Explosive, &GlobalTransform, &ThrownItem)>,
+    contacts: Query<(Has<Sensor>, Option<&Collected>)>,
+    teams: Teams,
+    defenses: Defenses,
+    mut targets: Query<(
+        Entity,
+        &GlobalTransform,
+        Option<&mut Health>,
+        Option<&RigidBody>,
+    )>,
+) {
+    let mut exploded = HashSet::new();
+    for CollisionStarted(a, b) in events.read() {
+        for (entity, other) in [(*a, *b), (*b, *a)] {
+            let Ok((explosive, gtr, thrown)) = explosives.get(entity) else {
+                continue;
+            };
+            // Items still held by the thrower's collector don't belong to the
+            // impact, neither do sensors
+            let ignored = |target| {
+                contacts.get(target).is_ok_and(|(sensor, collected)| {
+                    sensor
+                        || collected.is_some_and(|c| c.collector_entity == thrown.collector_entity)
+                })
+            };
+            if ignored(other) || !exploded.insert(entity) {
+                continue;
+            }
+            let center = gtr.translation();
+            // The thrower is not affected by its own explosions
+            let thrower = teams
+                .parents
+                .get(thrown.collector_entity)
+                .map(Parent::get)
+                .ok();
+            for (target, target_gtr, health, body) in &mut targets {
+                if target == entity || Some(target) == thrower || ignored(target) {
+                    continue;
+                }
+                let delta = target_gtr.translation() - center;
+                let distance = delta.length();
+                if distance > explosive.radius {
+                    continue;
+                }
+                let falloff = 1.0 - distance / explosive.radius;
+                let amount = friendly_fire.apply(
+                    (f32::from(explosive.damage) * falloff).round() as u16,
+                    teams.collector_team(thrown.collector_entity),
+                    teams.team(target),
+                );
+                let amount = defenses.mitigate(target, amount, DamageType::Explosive);
+                if let Some(mut health) = health.filter(|_| amount > 0) {
+                    let killed = health.damage_killed(amount);
+                    damage_evw.send(DamageDealt {
+                        source: entity,
+                        instigator: thrower.unwrap_or(entity),
+                        target,
+                        amount,
+                        killed,
+                    });
+                }
+                if body.is_some_and(RigidBody::is_dynamic) {
+                    let direction = delta.try_normalize().unwrap_or(Vec3::Y);
+                    commands.add(add_impulse(target, direction * explosive.impulse * falloff));
+                }
+            }
+            commands.entity(entity).insert(Dead);
+        }
+    }
+}
+
+fn stick_thrown_items(
+    mut commands: Commands,
+    mut events: EventReader<CollisionStarted>,
+    items: Query<(&Sticky, &GlobalTransform), (With<ThrownItem>, Without<Stuck>)>,
+    targets: Query<&GlobalTransform, Without<Sensor>>,
+) {
+    for CollisionStarted(a, b) in events.read() {
+        for (item, target) in [(*a, *b), (*b, *a)] {
+            let Ok((sticky, gtr)) = items.get(item) else {
+                continue;
+            };
+            let Ok(target_gtr) = targets.get(target) else {
+                continue;
+            };
+            commands.entity(item).remove::<ThrownItem>().insert(Stuck {
+                target,
+                offset: gtr.reparented_to(target_gtr),
+                timer: Timer::from_seconds(sticky.duration, TimerMode::Once),
+            });
+        }
+    }
+}
+
+fn update_stuck_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut items: Query<(Entity, &mut Stuck, &mut Transform, Has<Collected>)>,
+    targets: Query<&GlobalTransform>,
+) {
+    for (entity, mut stuck, mut transform, collected) in &mut items {
+        let finished = stuck.timer.tick(time.delta()).finished();
+        let Ok(target_gtr) = targets.get(stuck.target) else {
+            commands.entity(entity).remove::<Stuck>();
+            continue;
+        };
+        if finished || collected {
+            commands.entity(entity).remove::<Stuck>();
+            continue;
+        }
+        *transform = target_gtr.mul_transform(stuck.offset).compute_transform();
+    }
+}
+
+fn bounce_thrown_items(
+    mut events: EventReader<CollisionStarted>,
+    mut items: Query<(&mut Bouncy, &mut ThrownItem)>,
+) {
+    for (mut bouncy, thrown) in &mut items {
+        if thrown.is_added() {
+            bouncy.remaining = bouncy.bounces;
+        }
+    }
+    for CollisionStarted(a, b) in events.read() {
+        for entity in [*a, *b] {
+            let Ok((mut bouncy, mut thrown)) = items.get_mut(entity) else {
+                continue;
+            };
+            if bouncy.remaining == 0 {
+                continue;
+            }
+            bouncy.remaining -= 1;
+            thrown.reset_timer();
+        }
+    }
+}
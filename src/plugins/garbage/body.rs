@@ -1,8 +1,19 @@
 use std::f32::consts::PI;
 
-use bevy::prelude::*;
+use avian3d::prelude::*;
+use bevy::{ecs::system::SystemParam, log, prelude::*};
 
-use crate::plugins::garbage::PointDistribution;
+use crate::{
+    plugins::garbage::{Collected, PointDistribution},
+    ObjectLayer,
+};
+
+/// Maximum amount of FABRIK iterations for chains anchored at both ends
+const FABRIK_ITERATIONS: usize = 4;
+/// Tail distance to its anchor under which the FABRIK solve stops
+const FABRIK_TOLERANCE: f32 = 0.05;
+/// Height from which the ground is probed above chain points
+const GROUND_PROBE: f32 = 5.0;
 
 pub struct GarbageBodyPlugin;
 
@@ -34,26 +45,77 @@ pub struct Chain {
     // rads
     max_angle: f32,
     min_y: f32,
+    /// If set, the last point is anchored to this position
+    tail_anchor: Option<Vec3>,
 }
 
 impl Chain {
     #[inline]
-    pub fn len(&self) -> usize {
+    pub const fn len(&self) -> usize {
         self.points.len()
     }
 
     #[inline]
-    pub fn is_empty(&self) -> bool {
+    pub const fn is_empty(&self) -> bool {
         self.points.is_empty()
     }
 
+    /// Anchors the last point of the chain to `anchor`, making the chain
+    /// solve from both ends
+    #[inline]
+    pub const fn set_tail_anchor(&mut self, anchor: Option<Vec3>) {
+        self.tail_anchor = anchor;
+    }
+
+    #[inline]
+    pub const fn tail_anchor(&self) -> Option<Vec3> {
+        self.tail_anchor
+    }
+
     pub fn update(&mut self) {
+        self.solve(|_, position| position);
+    }
+
+    /// Updates the chain from its head, and from its tail if it is anchored.
+    ///
+    /// `constrain` is applied to every moved point, receiving the position
+    /// of the point it follows and its candidate position, and returning the
+    /// constrained position.
+    pub fn solve(&mut self, constrain: impl Fn(Vec3, Vec3) -> Vec3) {
         let len = self.points.len();
         if len <= 1 {
             return;
         }
+        let Some(tail) = self.tail_anchor else {
+            self.forward_pass(&constrain);
+            return;
+        };
+        let head = self.points[0].position;
+        for _ in 0..FABRIK_ITERATIONS {
+            self.backward_pass(tail, &constrain);
+            self.points[0].position = head;
+            self.forward_pass(&constrain);
+            if self.points[len - 1].position.distance_squared(tail) < FABRIK_TOLERANCE.powi(2) {
+                break;
+            }
+        }
+    }
+
+    /// Moves every point towards the tail anchor, from the tail
+    fn backward_pass(&mut self, tail: Vec3, constrain: &impl Fn(Vec3, Vec3) -> Vec3) {
+        let len = self.points.len();
+        self.points[len - 1].position = tail;
+        for i in (0..len - 1).rev() {
+            let next_pos = self.points[i + 1].position;
+            let current_point = &mut self.points[i];
+            let direction = Dir3::new(current_point.position - next_pos).unwrap_or(Dir3::NEG_Z);
+            current_point.position = constrain(next_pos, next_pos + direction * self.point_radius);
+        }
+    }
 
-        for i in 1..len {
+    /// Moves every point towards the head, from the head
+    fn forward_pass(&mut self, constrain: &impl Fn(Vec3, Vec3) -> Vec3) {
+        for i in 1..self.points.len() {
             let prev_pos = self.points[i - 1].position;
             let prev_dir = self.points[i - 1].direction;
             let current_point = &mut self.points[i];
@@ -78,6 +140,14 @@ impl Chain {
             if current_point.position.y < self.min_y {
                 current_point.position.y = self.min_y;
             }
+
+            // Apply external constraints
+            let position = constrain(prev_pos, current_point.position);
+            if position != current_point.position {
+                current_point.position = position;
+                current_point.direction =
+                    Dir3::new(position - prev_pos).unwrap_or(current_point.direction);
+            }
         }
     }
 
@@ -104,63 +174,264 @@ impl Chain {
             point_radius,
             max_angle,
             min_y,
+            tail_anchor: None,
+        }
+    }
+}
+
+/// Interaction of [`GarbageBody`] chains with the [`TerrainColliders`]
+#[derive(Debug, Clone, Copy, Reflect)]
+pub struct ChainTerrain {
+    /// Distance kept between chain points and the map geometry
+    pub clearance: f32,
+    /// If set, chain points are kept at least this high above the ground
+    pub ground_height: Option<f32>,
+}
+
+impl ChainTerrain {
+    /// Returns `to` constrained to not cross the map geometry when moving
+    /// from `from`, and to stay above the ground.
+    ///
+    /// `cast_ray` casts a ray against the map geometry, from an origin, in a
+    /// direction, up to a maximum distance and with the given solidity.
+    fn constrain(
+        &self,
+        cast_ray: &impl Fn(Vec3, Dir3, f32, bool) -> Option<RayHitData>,
+        from: Vec3,
+        to: Vec3,
+    ) -> Vec3 {
+        let mut position = to;
+        if let Ok((direction, distance)) = Dir3::new_and_length(to - from) {
+            if let Some(hit) = cast_ray(from, direction, distance + self.clearance, true) {
+                let distance = (hit.time_of_impact - self.clearance).max(0.0);
+                position = from + direction * distance + hit.normal * self.clearance;
+            }
         }
+        if let Some(height) = self.ground_height {
+            // The probe origin may be inside the map geometry, a solid ray would
+            // hit it right away
+            let origin = position + Vec3::Y * GROUND_PROBE;
+            if let Some(hit) = cast_ray(origin, Dir3::NEG_Y, GROUND_PROBE * 2.0, false) {
+                position.y = position.y.max(origin.y - hit.time_of_impact + height);
+            }
+        }
+        position
     }
 }
 
+/// Chain of a [`GarbageBody`] a [`Limb`] is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum LimbParent {
+    Dorsal,
+    /// Index of a previous limb of the body
+    Limb(usize),
+}
+
+/// A chain attached to a point of another chain of a [`GarbageBody`], like a
+/// leg, an arm, a fin or a tentacle branch
+#[derive(Debug, Reflect)]
+pub struct Limb {
+    pub chain: Chain,
+    pub parent: LimbParent,
+    /// Index of the attachment point on the parent chain
+    pub anchor: usize,
+    /// Direction of the limb root, relative to the anchor point where `Z` is
+    /// the anchor direction and `Y` is up
+    pub direction: Dir3,
+}
+
+/// Rotation from the local frame of a chain point, where `Z` is the point
+/// direction and `Y` is up, to world space
+fn point_frame(direction: Dir3) -> Quat {
+    let z = *direction;
+    let x = Vec3::Y.cross(z).try_normalize().unwrap_or(Vec3::X);
+    let y = z.cross(x);
+    Quat::from_mat3(&Mat3::from_cols(x, y, z))
+}
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct GarbageBody {
     // Anchored to Transform translation
     pub dorsal: Chain,
+    /// Chains attached to the dorsal or other limbs, updated in order
+    pub limbs: Vec<Limb>,
     pub offset: f32,
+    /// If set, chains collide with the [`TerrainColliders`]
+    pub terrain: Option<ChainTerrain>,
 }
 
 impl GarbageBody {
     pub fn new(amount: usize, pos: Vec3, radius: f32, offset: f32) -> Self {
         Self {
             dorsal: Chain::new(amount, pos, radius, PI, 1.0),
+            limbs: Vec::new(),
             offset,
+            terrain: None,
         }
     }
 
+    /// Makes the body chains collide with the [`TerrainColliders`], keeping
+    /// `clearance` from them and staying `ground_height` above the ground
+    #[must_use]
+    pub const fn with_terrain(mut self, clearance: f32, ground_height: Option<f32>) -> Self {
+        self.terrain = Some(ChainTerrain {
+            clearance,
+            ground_height,
+        });
+        self
+    }
+
+    /// Attaches a `chain` to the `anchor` point of the `parent` chain. Limbs
+    /// may only be attached to previously added limbs.
+    #[must_use]
+    pub fn with_limb(
+        mut self,
+        parent: LimbParent,
+        anchor: usize,
+        direction: Dir3,
+        chain: Chain,
+    ) -> Self {
+        self.limbs.push(Limb {
+            chain,
+            parent,
+            anchor,
+            direction,
+        });
+        self
+    }
+
     pub fn full_length(&self) -> f32 {
         self.dorsal.len() as f32 * self.dorsal.point_radius
     }
 
-    pub fn compute_3d_positions(&self, len: usize, distribution: &PointDistribution) -> Vec<Vec3> {
-        let mut res = Vec::with_capacity(len);
+    /// Iterates over the points of every chain, dorsal first
+    fn points(&self) -> impl Iterator<Item = &Point> {
+        self.dorsal
+            .points
+            .iter()
+            .chain(self.limbs.iter().flat_map(|limb| limb.chain.points.iter()))
+    }
+
+    /// Updates the limbs from their anchor points
+    fn update_limbs(&mut self, constrain: impl Fn(Vec3, Vec3) -> Vec3) {
+        for i in 0..self.limbs.len() {
+            let (previous, next) = self.limbs.split_at_mut(i);
+            let limb = &mut next[0];
+            let parent = match limb.parent {
+                LimbParent::Dorsal => &self.dorsal,
+                LimbParent::Limb(index) => {
+                    let Some(parent) = previous.get(index) else {
+                        log::error!("Limb {i} is attached to limb {index} which is not before it");
+                        continue;
+                    };
+                    &parent.chain
+                }
+            };
+            let Some(anchor) = parent.points.get(limb.anchor) else {
+                log::error!("Limb {i} anchor {} is out of its parent chain", limb.anchor);
+                continue;
+            };
+            let Some(root) = limb.chain.points.first_mut() else {
+                continue;
+            };
+            let direction = point_frame(anchor.direction) * *limb.direction;
+            root.direction = Dir3::new(direction).unwrap_or(anchor.direction);
+            root.position = anchor.position + *root.direction * limb.chain.point_radius;
+            limb.chain.solve(&constrain);
+        }
+    }
+
+    /// Appends the positions of `len` items distributed along the body to `res`
+    pub fn extend_3d_positions(
+        &self,
+        len: usize,
+        distribution: &PointDistribution,
+        res: &mut Vec<Vec3>,
+    ) {
         let mut i = 0_usize;
-        let mut j = 0_usize;
-        while i < len && j < self.dorsal.len() {
-            let dorsal_point = &self.dorsal.points[j];
-            let rot = Quat::from_rotation_arc(Vec3::Y, *dorsal_point.direction);
+        for chain_point in self.points() {
+            if i >= len {
+                break;
+            }
+            let rot = Quat::from_rotation_arc(Vec3::Y, *chain_point.direction);
             let points = distribution.points();
             for p in points {
-                let p = rot * Vec3::new(p.x, 0.0, p.y);
-                res.push(dorsal_point.position + p);
+                res.push(chain_point.position + rot * *p);
             }
             i += points.len();
-            j += 1;
         }
-        res
     }
 }
 
-fn update_bodies(mut bodies: Query<(&GlobalTransform, &mut GarbageBody)>) {
+/// Colliders [`GarbageBody`] chains collide with: the map geometry and the
+/// items lying around, like the ones of builds. Sensors, like the game
+/// starters, and collected items, which move with their collector, are not
+/// terrain.
+#[derive(SystemParam)]
+pub struct TerrainColliders<'w, 's> {
+    colliders: Query<'w, 's, (&'static CollisionLayers, Has<Sensor>, Has<Collected>)>,
+}
+
+impl TerrainColliders<'_, '_> {
+    /// Returns `true` if chains collide with `entity`
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.colliders
+            .get(entity)
+            .is_ok_and(|(layers, sensor, collected)| {
+                let terrain = layers.memberships.has_all(ObjectLayer::Map)
+                    || layers.memberships.has_all(ObjectLayer::Collectible) && !collected;
+                terrain && !sensor
+            })
+    }
+
+    /// Casts a ray against the terrain colliders
+    pub fn cast_ray(
+        &self,
+        spatial: &SpatialQuery,
+        origin: Vec3,
+        direction: Dir3,
+        distance: f32,
+        solid: bool,
+    ) -> Option<RayHitData> {
+        // The layers are checked by the predicate: the filter is taken by value
+        // and the default one excludes no entity, so it doesn't allocate
+        spatial.cast_ray_predicate(
+            origin,
+            direction,
+            distance,
+            solid,
+            SpatialQueryFilter::default(),
+            &|entity| self.contains(entity),
+        )
+    }
+}
+
+fn update_bodies(
+    mut bodies: Query<(&GlobalTransform, &mut GarbageBody)>,
+    spatial: SpatialQuery,
+    terrain_colliders: TerrainColliders,
+) {
+    let cast_ray = |origin, direction, distance, solid| {
+        terrain_colliders.cast_ray(&spatial, origin, direction, distance, solid)
+    };
     for (gtr, mut body) in &mut bodies {
         let forward = gtr.forward();
         body.dorsal.points[0].position = gtr.translation() + forward * body.offset;
         body.dorsal.points[0].direction = forward;
-        body.dorsal.update();
+        let terrain = body.terrain;
+        let constrain =
+            |from, to| terrain.map_or(to, |terrain| terrain.constrain(&cast_ray, from, to));
+        body.dorsal.solve(constrain);
+        body.update_limbs(constrain);
     }
 }
 
 #[cfg(feature = "debug")]
 fn draw_gizmos(mut gizmos: Gizmos, bodies: Query<&GarbageBody>) {
     for body in &bodies {
-        // dorsal
-        for point in &body.dorsal.points {
+        // dorsal and limbs
+        for point in body.points() {
             // gizmos.circle(
             //     point.position,
             //     point.direction,
@@ -175,3 +446,152 @@ fn draw_gizmos(mut gizmos: Gizmos, bodies: Query<&GarbageBody>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+
+    fn chain(anchor: Vec3) -> Chain {
+        let mut chain = Chain::new(6, Vec3::ZERO, 1.0, PI, f32::MIN);
+        chain.set_tail_anchor(Some(anchor));
+        chain.update();
+        chain
+    }
+
+    fn assert_segments(chain: &Chain) {
+        for pair in chain.points.windows(2) {
+            let length = pair[0].position.distance(pair[1].position);
+            assert!((length - chain.point_radius).abs() < 1e-3, "{length}");
+        }
+    }
+
+    #[test]
+    fn fabrik_reaches_tail_anchor() {
+        let anchor = Vec3::new(3.0, 0.0, 2.0);
+        let chain = chain(anchor);
+        assert_eq!(chain.points[0].position, Vec3::ZERO);
+        let tail = chain.points[chain.len() - 1].position;
+        assert!(tail.distance(anchor) < FABRIK_TOLERANCE, "{tail}");
+        assert_segments(&chain);
+    }
+
+    #[test]
+    fn fabrik_stretches_towards_unreachable_anchor() {
+        let anchor = Vec3::new(0.0, 0.0, 20.0);
+        let chain = chain(anchor);
+        assert_eq!(chain.points[0].position, Vec3::ZERO);
+        for (i, point) in chain.points.iter().enumerate() {
+            assert!(point.position.distance(Vec3::Z * i as f32) < 1e-3);
+        }
+        assert_segments(&chain);
+    }
+
+    /// Casts rays against the `y = 0` ground plane and an axis aligned box
+    /// from `min` to `max`. Like the physics engine, rays starting inside the
+    /// box hit it right away if `solid`, or hit its boundary otherwise.
+    fn cast_ray(min: Vec3, max: Vec3) -> impl Fn(Vec3, Dir3, f32, bool) -> Option<RayHitData> {
+        move |origin, direction, max_distance, solid| {
+            let ground =
+                (origin.y >= 0.0 && direction.y < 0.0).then(|| (origin.y / -direction.y, Vec3::Y));
+            let t1 = (min - origin) / *direction;
+            let t2 = (max - origin) / *direction;
+            let (near, far) = (t1.min(t2), t1.max(t2));
+            let (enter, exit) = (near.max_element(), far.min_element());
+            let axis = |t: Vec3, value: f32| {
+                Vec3::select(t.cmpeq(Vec3::splat(value)), Vec3::ONE, Vec3::ZERO)
+            };
+            let obstacle = if exit < enter.max(0.0) {
+                None
+            } else if enter >= 0.0 {
+                Some((enter, -axis(near, enter) * direction.signum()))
+            } else if solid {
+                Some((0.0, Vec3::ZERO))
+            } else {
+                Some((exit, axis(far, exit) * direction.signum()))
+            };
+            [ground, obstacle]
+                .into_iter()
+                .flatten()
+                .filter(|(distance, _)| *distance <= max_distance)
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(time_of_impact, normal)| RayHitData {
+                    entity: Entity::PLACEHOLDER,
+                    time_of_impact,
+                    normal,
+                })
+        }
+    }
+
+    #[test]
+    fn terrain_blocks_chain_points() {
+        let terrain = ChainTerrain {
+            clearance: 0.1,
+            ground_height: None,
+        };
+        let cast_ray = cast_ray(Vec3::new(2.0, 0.0, -2.0), Vec3::new(4.0, 4.0, 2.0));
+        let position = terrain.constrain(&cast_ray, Vec3::Y, Vec3::new(3.0, 1.0, 0.0));
+        assert!(
+            position.distance(Vec3::new(1.8, 1.0, 0.0)) < 1e-3,
+            "{position}"
+        );
+    }
+
+    #[test]
+    fn terrain_keeps_ground_height() {
+        let terrain = ChainTerrain {
+            clearance: 0.1,
+            ground_height: Some(0.5),
+        };
+        let cast_ray = cast_ray(Vec3::splat(10.0), Vec3::splat(11.0));
+        let position = terrain.constrain(&cast_ray, Vec3::Y, Vec3::new(1.0, 0.2, 0.0));
+        assert!(
+            position.distance(Vec3::new(1.0, 0.5, 0.0)) < 1e-3,
+            "{position}"
+        );
+    }
+
+    #[test]
+    fn ground_probe_ignores_enclosing_geometry() {
+        let terrain = ChainTerrain {
+            clearance: 0.1,
+            ground_height: Some(0.5),
+        };
+        // The probe starts inside the box, the point doesn't jump on top of it
+        let cast_ray = cast_ray(Vec3::new(-1.0, 0.0, -1.0), Vec3::new(1.0, 10.0, 1.0));
+        let point = Vec3::new(0.0, 2.0, 0.0);
+        assert_eq!(terrain.constrain(&cast_ray, point, point), point);
+    }
+
+    #[test]
+    fn terrain_includes_loose_items() {
+        let mut world = World::new();
+        let map = CollisionLayers::new(ObjectLayer::Map, LayerMask::ALL);
+        let item = CollisionLayers::new(ObjectLayer::Collectible, LayerMask::ALL);
+        let wall = world.spawn(map).id();
+        let starter = world.spawn((map, Sensor)).id();
+        let build_item = world.spawn(item).id();
+        let player = world
+            .spawn(CollisionLayers::new(ObjectLayer::Player, LayerMask::ALL))
+            .id();
+        let collector = world.spawn(GlobalTransform::default()).id();
+        let collected_item = world
+            .spawn((
+                item,
+                GlobalTransform::default(),
+                Collected {
+                    collector_entity: collector,
+                },
+            ))
+            .id();
+
+        let mut state = SystemState::<TerrainColliders>::new(&mut world);
+        let terrain = state.get(&world);
+        assert!(terrain.contains(wall));
+        assert!(terrain.contains(build_item));
+        assert!(!terrain.contains(starter));
+        assert!(!terrain.contains(player));
+        assert!(!terrain.contains(collected_item));
+    }
+}
@@ -1,12 +1,26 @@
-use super::{GarbageAssets, GarbageBundle, GarbageItem};
+use super::{
+    items::{GarbageCatalog, CATALOG_PATH},
+    GarbageAssets, GarbageBundle, GarbageItem,
+};
 use bevy::{
-    asset::{AssetLoader, AsyncReadExt, LoadedFolder, RecursiveDependencyLoadState},
+    asset::{AssetLoader, AsyncReadExt, LoadDirectError, LoadedFolder},
     ecs::world::Command,
+    log,
     prelude::*,
     utils::HashMap,
 };
+use serde::{Deserialize, Serialize};
+use std::{
+    f32::consts::{FRAC_PI_2, PI},
+    fmt,
+};
 use thiserror::Error;
 
+/// Asset folder containing the `.build` files
+const BUILDS_FOLDER: &str = "builds";
+/// Distance between two grid cells of a build
+const CELL_SIZE: f32 = 1.05;
+
 pub struct ItemBuildsPlugin;
 
 impl Plugin for ItemBuildsPlugin {
@@ -16,10 +30,7 @@ impl Plugin for ItemBuildsPlugin {
             .init_asset_loader::<BuildLoader>()
             .init_resource::<AvailableItemBuilds>()
             .add_systems(Startup, setup_builds)
-            .add_systems(
-                Update,
-                tick_loading_builds.run_if(resource_exists::<LoadingFolder>),
-            );
+            .add_systems(Update, track_builds);
     }
 }
 
@@ -31,23 +42,276 @@ pub struct ItemBuild {
 #[derive(Clone, Debug)]
 struct ItemSlot {
     pub item: GarbageItem,
+    /// Size of the item, from the catalog the build was parsed with
+    pub size: Vec3,
     pub position: Vec3,
-    pub y_angle: f32,
+    pub orientation: ItemOrientation,
+}
+
+impl ItemSlot {
+    /// Amount of neighbouring grid cells covered by the item, along `X` and
+    /// `Z`
+    fn reach(&self) -> Vec2 {
+        let extents = self.orientation.extents(self.size);
+        Vec2::new(
+            (extents.x / 2.0 - 0.5).ceil(),
+            (extents.z / 2.0 - 0.5).ceil(),
+        )
+    }
+
+    /// Returns `true` if both slots horizontally overlap
+    fn overlaps(&self, other: &Self) -> bool {
+        let reach = self.reach() + other.reach();
+        (self.position.x - other.position.x).abs() <= reach.x
+            && (self.position.z - other.position.z).abs() <= reach.y
+    }
+
+    /// Returns `true` if `other` is below `self` and close enough to support it
+    fn is_supported_by(&self, other: &Self) -> bool {
+        let bottom = self.position.y - self.orientation.extents(self.size).y / 2.0;
+        let top = other.position.y + other.orientation.extents(other.size).y / 2.0;
+        other.position.y < self.position.y && bottom - top < MAX_SUPPORT_GAP && self.overlaps(other)
+    }
+}
+
+/// Orientation of a build item, defined in the optional orientation layer of
+/// a slice
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ItemOrientation {
+    /// Default orientation (`.`)
+    #[default]
+    Upright,
+    /// Quarter turn around the vertical axis, spanning along `Z` (`>`)
+    Turned,
+    /// Lying on its side along `X` (`-`)
+    Lying,
+    /// Lying on its side along `Z` (`=`)
+    LyingTurned,
+    /// Upside down (`v`)
+    Flipped,
+}
+
+impl ItemOrientation {
+    /// Returns the closest orientation to `rotation`
+    pub fn from_rotation(rotation: Quat) -> Self {
+        let up = rotation * Vec3::Y;
+        let right = rotation * Vec3::X;
+        if up.y.abs() >= up.x.abs().max(up.z.abs()) {
+            if up.y < 0.0 {
+                Self::Flipped
+            } else if right.z.abs() > right.x.abs() {
+                Self::Turned
+            } else {
+                Self::Upright
+            }
+        } else if up.x.abs() > up.z.abs() {
+            Self::Lying
+        } else {
+            Self::LyingTurned
+        }
+    }
+
+    pub const fn to_char(self) -> char {
+        match self {
+            Self::Upright => '.',
+            Self::Turned => '>',
+            Self::Lying => '-',
+            Self::LyingTurned => '=',
+            Self::Flipped => 'v',
+        }
+    }
+
+    pub const fn from_char(c: char) -> Option<Self> {
+        let orientation = match c {
+            '.' | ' ' => Self::Upright,
+            '>' => Self::Turned,
+            '-' => Self::Lying,
+            '=' => Self::LyingTurned,
+            'v' => Self::Flipped,
+            _ => return None,
+        };
+        Some(orientation)
+    }
+
+    pub fn rotation(self) -> Quat {
+        match self {
+            Self::Upright => Quat::IDENTITY,
+            Self::Turned => Quat::from_rotation_y(FRAC_PI_2),
+            Self::Lying => Quat::from_rotation_z(FRAC_PI_2),
+            Self::LyingTurned => Quat::from_rotation_x(FRAC_PI_2),
+            Self::Flipped => Quat::from_rotation_x(PI),
+        }
+    }
+
+    /// Returns the axis aligned `size` of an item once oriented
+    pub const fn extents(self, size: Vec3) -> Vec3 {
+        match self {
+            Self::Upright | Self::Flipped => size,
+            Self::Turned => Vec3::new(size.z, size.y, size.x),
+            Self::Lying => Vec3::new(size.y, size.x, size.z),
+            Self::LyingTurned => Vec3::new(size.x, size.z, size.y),
+        }
+    }
+}
+
+/// Checks that every glyph of `rows` is `valid` and that all rows share the
+/// same width. `rows` are expected bottom up.
+fn check_rows(rows: &[(usize, &str)], valid: impl Fn(char) -> bool) -> Result<(), BuildAssetError> {
+    let Some(expected) = rows.last().map(|(_, row)| row.chars().count()) else {
+        return Ok(());
+    };
+    for &(line, row) in rows.iter().rev() {
+        if let Some((column, glyph)) = row
+            .chars()
+            .zip(1..)
+            .find_map(|(c, column)| (!valid(c)).then_some((column, c)))
+        {
+            return Err(BuildAssetError::UnknownGlyph {
+                glyph,
+                line,
+                column,
+            });
+        }
+        let found = row.chars().count();
+        if found != expected {
+            return Err(BuildAssetError::RaggedRow {
+                line,
+                expected,
+                found,
+            });
+        }
+    }
+    Ok(())
 }
 
+/// Line separating two depth slices of a `.build` file
+const SLICE_SEPARATOR: &str = "---";
+/// Line separating the item rows of a slice from its orientation rows
+const ORIENTATION_SEPARATOR: &str = "~~~";
+/// Maximum vertical gap, in grid cells, an item may fall before resting on its
+/// support
+const MAX_SUPPORT_GAP: f32 = 2.0;
+
 impl ItemBuild {
-    pub fn parse(bytes: &[u8]) -> Result<Self, BuildAssetError> {
+    /// Parses a `.build` file.
+    ///
+    /// Every line is a horizontal row of items, the last line being the
+    /// ground row. A file may contain multiple depth slices separated by a
+    /// `---` line, the first slice being the front one (`z = 0`) and each
+    /// following slice being placed one step further along `Z`.
+    ///
+    /// A slice may end with an orientation layer, introduced by a `~~~` line,
+    /// whose rows match the item rows from the bottom up. Each glyph sets the
+    /// [`ItemOrientation`] of the item at the same position.
+    ///
+    /// Item glyphs are defined by the `catalog`. Unknown glyphs are ignored,
+    /// see [`Self::parse_strict`] for a validating parser.
+    pub fn parse(bytes: &[u8], catalog: &GarbageCatalog) -> Result<Self, BuildAssetError> {
+        Self::parse_with(bytes, catalog, false)
+    }
+
+    /// Parses a `.build` file like [`Self::parse`], but rejects unknown
+    /// glyphs, ragged rows, orientation layers not matching the count or width
+    /// of their item rows,
+    /// floating items and empty builds.
+    pub fn parse_strict(bytes: &[u8], catalog: &GarbageCatalog) -> Result<Self, BuildAssetError> {
+        Self::parse_with(bytes, catalog, true)
+    }
+
+    fn parse_with(
+        bytes: &[u8],
+        catalog: &GarbageCatalog,
+        strict: bool,
+    ) -> Result<Self, BuildAssetError> {
         let mut items = Vec::new();
+        // Source `(line, column)` of every item, for diagnostics
+        let mut locations = Vec::new();
         let content = std::str::from_utf8(bytes)?;
-        let layers = content.lines().rev().enumerate();
-
-        for (y, layer) in layers {
-            for (x, c) in layer.chars().enumerate() {
-                if let Some(item) = GarbageItem::from_char(c) {
-                    items.push(ItemSlot {
-                        item,
-                        position: Vec3::new(x as f32, y as f32 + 0.5, 0.0),
-                        y_angle: 0.0,
+        let lines: Vec<(usize, &str)> = content.lines().zip(1..).map(|(l, i)| (i, l)).collect();
+        let slices = lines.split(|(_, line)| line.trim() == SLICE_SEPARATOR);
+
+        for (z, slice) in slices.enumerate() {
+            let (layers, orientations, separator) = match slice
+                .iter()
+                .position(|(_, line)| line.trim() == ORIENTATION_SEPARATOR)
+            {
+                Some(i) => (&slice[..i], &slice[i + 1..], Some(slice[i].0)),
+                None => (slice, &[][..], None),
+            };
+            // Ignore blank lines preceding a separator
+            let orientations: Vec<_> = orientations
+                .iter()
+                .rev()
+                .skip_while(|(_, row)| row.is_empty())
+                .copied()
+                .collect();
+            let layers: Vec<_> = layers
+                .iter()
+                .rev()
+                .skip_while(|(_, layer)| layer.is_empty())
+                .copied()
+                .collect();
+            if strict {
+                check_rows(&layers, |c| {
+                    c == '.' || c == ' ' || GarbageItem::from_char(c, catalog).is_some()
+                })?;
+                check_rows(&orientations, |c| ItemOrientation::from_char(c).is_some())?;
+                if let Some(line) = separator.filter(|_| orientations.len() != layers.len()) {
+                    return Err(BuildAssetError::OrientationRows {
+                        line,
+                        column: 1,
+                        expected: layers.len(),
+                        found: orientations.len(),
+                    });
+                }
+                for (&(line, row), (_, layer)) in orientations.iter().zip(&layers) {
+                    let (expected, found) = (layer.chars().count(), row.chars().count());
+                    if found != expected {
+                        return Err(BuildAssetError::OrientationRows {
+                            line,
+                            column: expected.min(found) + 1,
+                            expected,
+                            found,
+                        });
+                    }
+                }
+            }
+            for (y, (line, layer)) in layers.into_iter().enumerate() {
+                let orientation_row = orientations.get(y).map(|(_, row)| row);
+                for ((x, c), column) in layer.chars().enumerate().zip(1..) {
+                    if let Some(item) = GarbageItem::from_char(c, catalog) {
+                        let orientation = orientation_row
+                            .and_then(|row| row.chars().nth(x))
+                            .and_then(ItemOrientation::from_char)
+                            .unwrap_or_default();
+                        items.push(ItemSlot {
+                            item,
+                            size: catalog[item].shape.size(),
+                            position: Vec3::new(x as f32, y as f32 + 0.5, z as f32),
+                            orientation,
+                        });
+                        locations.push((line, column));
+                    }
+                }
+            }
+        }
+
+        if strict {
+            if items.is_empty() {
+                return Err(BuildAssetError::Empty);
+            }
+            let ground = items
+                .iter()
+                .map(|slot| slot.position.y)
+                .fold(f32::MAX, f32::min);
+            for (slot, (line, column)) in items.iter().zip(locations) {
+                let supported = slot.position.y <= ground
+                    || items.iter().any(|other| slot.is_supported_by(other));
+                if !supported {
+                    return Err(BuildAssetError::FloatingItem {
+                        item: catalog[slot.item].name.clone(),
+                        line,
+                        column,
                     });
                 }
             }
@@ -56,20 +320,105 @@ impl ItemBuild {
         Ok(Self { items })
     }
 
+    /// Quantizes world space `items` into a build grid relative to `origin`.
+    ///
+    /// The resulting build is shifted so that its first column and slice are
+    /// at zero and its lowest items are on the ground row, as builds are
+    /// spawned on the ground. When several items fall in the same cell only
+    /// the first one is kept.
+    pub fn from_items(
+        origin: Transform,
+        items: impl IntoIterator<Item = (GarbageItem, Transform)>,
+        catalog: &GarbageCatalog,
+    ) -> Self {
+        let inverse = origin.compute_affine().inverse();
+        let mut cells: HashMap<IVec3, (GarbageItem, ItemOrientation)> = HashMap::new();
+        for (item, transform) in items {
+            let local = inverse.transform_point3(transform.translation) / CELL_SIZE;
+            let cell = (local - Vec3::Y * 0.5).round().as_ivec3();
+            let orientation =
+                ItemOrientation::from_rotation(origin.rotation.inverse() * transform.rotation);
+            if cells.contains_key(&cell) {
+                let name = &catalog[item].name;
+                log::warn!("Discarding `{name}` at {cell}, the cell is already occupied");
+                continue;
+            }
+            cells.insert(cell, (item, orientation));
+        }
+        let min = cells.keys().copied().reduce(IVec3::min).unwrap_or_default();
+        let mut items: Vec<_> = cells
+            .into_iter()
+            .map(|(cell, (item, orientation))| ItemSlot {
+                item,
+                size: catalog[item].shape.size(),
+                position: (cell - min).as_vec3() + Vec3::Y * 0.5,
+                orientation,
+            })
+            .collect();
+        // Keep a stable order
+        items.sort_by(|a, b| {
+            let [a, b] = [a, b].map(|s| (s.position.z, s.position.y, s.position.x));
+            a.partial_cmp(&b).unwrap()
+        });
+        Self { items }
+    }
+
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns a [`fmt::Display`] implementation writing the build in the
+    /// `.build` format, see [`Self::parse`]
+    pub const fn display<'a>(&'a self, catalog: &'a GarbageCatalog) -> BuildDisplay<'a> {
+        BuildDisplay {
+            build: self,
+            catalog,
+        }
+    }
+
+    /// Horizontal bounding circle of the build in local space, as a center
+    /// and a radius
+    pub fn footprint(&self) -> (Vec2, f32) {
+        if self.is_empty() {
+            return (Vec2::ZERO, 0.0);
+        }
+        let (min, max) = self
+            .items
+            .iter()
+            .fold((Vec2::MAX, Vec2::MIN), |(min, max), slot| {
+                let position = slot.position.xz();
+                let reach = slot.reach() + 0.5;
+                (min.min(position - reach), max.max(position + reach))
+            });
+        let center = (min + max) / 2.0 * CELL_SIZE;
+        (center, (max - min).length() / 2.0 * CELL_SIZE)
+    }
+
     pub fn spawn(self, position: Vec3, angle: f32) -> impl FnOnce(&mut World) {
         let transform =
             Transform::from_translation(position).with_rotation(Quat::from_rotation_y(angle));
         move |world| {
-            let assets = world.resource::<GarbageAssets>();
+            let Some(assets) = world.get_resource::<GarbageAssets>() else {
+                log::warn!("Garbage catalog is not loaded, can't spawn build");
+                return;
+            };
             let bundles: Vec<_> = self
                 .items
                 .iter()
+                // Kinds removed from the catalog since the build was loaded
+                .filter(|slot| assets.catalog.get(slot.item).is_some())
                 .map(|slot| {
-                    let pos = slot.position * 1.05;
+                    let pos = slot.position * CELL_SIZE;
                     let mut bundle = GarbageBundle::new(slot.item, assets);
                     bundle.pbr.transform.translation = transform.transform_point(pos);
                     bundle.pbr.transform.rotation =
-                        transform.rotation * Quat::from_rotation_y(slot.y_angle);
+                        transform.rotation * slot.orientation.rotation();
                     bundle
                 })
                 .collect();
@@ -78,6 +427,49 @@ impl ItemBuild {
     }
 }
 
+/// Writes a build in the `.build` format, see [`ItemBuild::display`]
+pub struct BuildDisplay<'a> {
+    build: &'a ItemBuild,
+    catalog: &'a GarbageCatalog,
+}
+
+impl fmt::Display for BuildDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cells: HashMap<IVec3, &ItemSlot> = self
+            .build
+            .items
+            .iter()
+            .map(|slot| ((slot.position - Vec3::Y * 0.5).round().as_ivec3(), slot))
+            .collect();
+        let max = cells.keys().copied().reduce(IVec3::max).unwrap_or_default();
+        for z in 0..=max.z {
+            if z > 0 {
+                writeln!(f, "{SLICE_SEPARATOR}")?;
+            }
+            let mut rows = String::new();
+            let mut orientations = String::new();
+            let mut oriented = false;
+            for y in (0..=max.y).rev() {
+                for x in 0..=max.x {
+                    let slot = cells.get(&IVec3::new(x, y, z));
+                    rows.push(slot.map_or('.', |slot| self.catalog[slot.item].glyph));
+                    let orientation = slot.map_or_else(default, |slot| slot.orientation);
+                    oriented |= orientation != ItemOrientation::Upright;
+                    orientations.push(orientation.to_char());
+                }
+                rows.push('\n');
+                orientations.push('\n');
+            }
+            f.write_str(&rows)?;
+            if oriented {
+                writeln!(f, "{ORIENTATION_SEPARATOR}")?;
+                f.write_str(&orientations)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct SpawnBuild {
     pub handle: Handle<ItemBuild>,
@@ -88,45 +480,124 @@ pub struct SpawnBuild {
 impl Command for SpawnBuild {
     fn apply(self, world: &mut World) {
         let builds = world.resource::<Assets<ItemBuild>>();
-        let build = builds.get(self.handle.id()).unwrap().clone();
+        let Some(build) = builds.get(self.handle.id()).cloned() else {
+            log::warn!(
+                "Build {:?} is not loaded, it can't be spawned",
+                self.handle.id()
+            );
+            return;
+        };
         build.spawn(self.position, self.angle)(world);
     }
 }
 
+/// Exports live garbage items to `assets/builds/<name>.build`
+#[derive(Debug)]
+pub struct SaveBuild {
+    pub entities: Vec<Entity>,
+    /// Reference frame of the build
+    pub origin: Transform,
+    pub name: String,
+}
+
+impl Command for SaveBuild {
+    fn apply(self, world: &mut World) {
+        let mut items = world.query::<(&GarbageItem, &GlobalTransform)>();
+        let Some(catalog) = world.get_resource::<GarbageAssets>().map(|a| &a.catalog) else {
+            log::warn!(
+                "Garbage catalog is not loaded, can't save build `{}`",
+                self.name
+            );
+            return;
+        };
+        let build = ItemBuild::from_items(
+            self.origin,
+            items
+                .iter_many(world, &self.entities)
+                .map(|(item, gtr)| (*item, gtr.compute_transform())),
+            catalog,
+        );
+        if build.is_empty() {
+            log::warn!("No garbage item to save in build `{}`", self.name);
+            return;
+        }
+        let content = build.display(catalog).to_string();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = bevy::asset::io::file::FileAssetReader::get_base_path()
+                .join("assets")
+                .join(BUILDS_FOLDER)
+                .join(format!("{}.build", self.name));
+            match std::fs::write(&path, content) {
+                Ok(()) => log::info!("Saved {} items to {}", build.len(), path.display()),
+                Err(e) => log::error!("Failed to save build to {}: {e}", path.display()),
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        log::error!("Saving builds is not supported on this platform:\n{content}");
+    }
+}
+
+/// Registry of the loaded builds, indexed by asset path.
+///
+/// Handles are weak, the builds are kept alive by [`BuildsFolder`] and the
+/// registry follows the [`ItemBuild`] asset events. With the `file_watcher`
+/// feature, added, edited and deleted files are reflected on the fly.
 #[derive(Resource, Deref, Default)]
-pub struct AvailableItemBuilds(HashMap<String, Handle<ItemBuild>>);
+pub struct AvailableItemBuilds(pub(super) HashMap<String, Handle<ItemBuild>>);
 
+/// Strong handle to the builds folder, the asset server reloads it when files
+/// are added or removed
 #[derive(Resource)]
-struct LoadingFolder(Handle<LoadedFolder>);
+struct BuildsFolder(Handle<LoadedFolder>);
 
 fn setup_builds(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.insert_resource(LoadingFolder(asset_server.load_folder("builds")));
+    commands.insert_resource(BuildsFolder(asset_server.load_folder(BUILDS_FOLDER)));
 }
 
-fn tick_loading_builds(
-    mut commands: Commands,
+fn track_builds(
+    mut events: EventReader<AssetEvent<ItemBuild>>,
     mut builds: ResMut<AvailableItemBuilds>,
-    loading: Res<LoadingFolder>,
     asset_server: Res<AssetServer>,
-    assets: Res<Assets<LoadedFolder>>,
 ) {
-    if Some(RecursiveDependencyLoadState::Loaded)
-        == asset_server.get_recursive_dependency_load_state(loading.0.id())
-    {
-        let folder: &LoadedFolder = assets.get(&loading.0).unwrap();
-        builds.0.extend(folder.handles.iter().cloned().map(|h| {
-            let path = h.path().unwrap().to_string();
-            let build = h.typed::<ItemBuild>();
-            (path, build)
-        }));
-        commands.remove_resource::<LoadingFolder>();
+    for event in events.read() {
+        match *event {
+            // Invalid builds never reach this state, the loader already reported their errors
+            AssetEvent::LoadedWithDependencies { id } => {
+                let Some(path) = asset_server.get_path(id) else {
+                    continue;
+                };
+                if !path.path().starts_with(BUILDS_FOLDER) {
+                    continue;
+                }
+                let path = path.to_string();
+                if builds.0.insert(path.clone(), Handle::Weak(id)).is_none() {
+                    log::info!("Registered build `{path}`");
+                }
+            }
+            AssetEvent::Modified { id } => {
+                if let Some(path) = asset_server.get_path(id) {
+                    log::info!("Reloaded build `{path}`");
+                }
+            }
+            AssetEvent::Removed { id } => {
+                builds.0.retain(|path, handle| {
+                    let keep = handle.id() != id;
+                    if !keep {
+                        log::info!("Unregistered build `{path}`");
+                    }
+                    keep
+                });
+            }
+            _ => {}
+        }
     }
 }
 
 #[derive(Default)]
 pub struct BuildLoader;
 
-/// Possible errors that can be produced by [`CustomAssetLoader`]
+/// Possible errors that can be produced by [`BuildLoader`]
 #[non_exhaustive]
 #[derive(Debug, Error)]
 pub enum BuildAssetError {
@@ -135,40 +606,259 @@ pub enum BuildAssetError {
     Io(#[from] std::io::Error),
     #[error("Invalid file: {0}")]
     Utf8(#[from] std::str::Utf8Error),
+    #[error("Could not load the item catalog: {0}")]
+    Catalog(#[from] Box<LoadDirectError>),
+    #[error("Unknown glyph `{glyph}` at line {line}, column {column}")]
+    UnknownGlyph {
+        glyph: char,
+        line: usize,
+        column: usize,
+    },
+    #[error("Row at line {line} has {found} columns, expected {expected}")]
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The orientation layer has a different amount of rows than the item
+    /// rows, or one of its rows a different width than its item row
+    #[error(
+        "Orientation layer does not match its item rows at line {line}, column {column}: \
+         expected {expected}, found {found}"
+    )]
+    OrientationRows {
+        line: usize,
+        column: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Floating `{item}` at line {line}, column {column} has no support below it")]
+    FloatingItem {
+        item: String,
+        line: usize,
+        column: usize,
+    },
+    #[error("Build has no items")]
+    Empty,
+}
+
+/// [`BuildLoader`] settings, configurable through `.build.meta` files
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BuildLoaderSettings {
+    /// Rejects malformed builds, see [`ItemBuild::parse_strict`]. Otherwise
+    /// they are loaded leniently, ignoring their issues, see
+    /// [`ItemBuild::parse`]
+    pub strict: bool,
+}
+
+impl Default for BuildLoaderSettings {
+    fn default() -> Self {
+        Self { strict: true }
+    }
 }
 
 impl AssetLoader for BuildLoader {
     type Asset = ItemBuild;
-    type Settings = ();
+    type Settings = BuildLoaderSettings;
     type Error = BuildAssetError;
 
     async fn load<'a>(
         &'a self,
         reader: &'a mut bevy::asset::io::Reader<'_>,
-        _settings: &'a Self::Settings,
-        _load_context: &'a mut bevy::asset::LoadContext<'_>,
+        settings: &'a Self::Settings,
+        load_context: &'a mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
-        let asset = ItemBuild::parse(&bytes)?;
+        // Builds are reloaded when the catalog changes
+        let catalog = load_context
+            .loader()
+            .direct()
+            .load::<GarbageCatalog>(CATALOG_PATH)
+            .await
+            .map_err(Box::new)?;
+        let catalog = catalog.get();
+        let asset = if settings.strict {
+            ItemBuild::parse_strict(&bytes, catalog)?
+        } else {
+            ItemBuild::parse(&bytes, catalog)?
+        };
         Ok(asset)
     }
 }
 
-impl GarbageItem {
-    pub const fn from_char(c: char) -> Option<Self> {
-        let item = match c {
-            'c' => Self::Cube,
-            'p' => Self::Plank,
-            'P' => Self::LargePlank,
-            '|' => Self::Column,
-            'I' => Self::LargeColumn,
-            'b' => Self::Block,
-            'B' => Self::LargeBlock,
-            '^' => Self::Cone,
-            'o' => Self::Ball,
-            _ => return None,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> GarbageCatalog {
+        ron::de::from_str(include_str!("../../../assets/items.catalog")).unwrap()
+    }
+
+    fn parse_strict(content: &str) -> Result<ItemBuild, BuildAssetError> {
+        ItemBuild::parse_strict(content.as_bytes(), &catalog())
+    }
+
+    #[test]
+    fn shipped_builds_are_valid() {
+        let catalog = catalog();
+        let folder = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join(BUILDS_FOLDER);
+        for entry in std::fs::read_dir(folder).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "build") {
+                let bytes = std::fs::read(&path).unwrap();
+                if let Err(e) = ItemBuild::parse_strict(&bytes, &catalog) {
+                    panic!("{}: {e}", path.display());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lenient_parsing_ignores_errors() {
+        let build = ItemBuild::parse(b"c#c\n.\n.\n.\nc..", &catalog()).unwrap();
+        assert_eq!(build.len(), 3);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let res = ItemBuild::parse_strict(&[b'c', 0xFF], &catalog());
+        assert!(matches!(res, Err(BuildAssetError::Utf8(_))));
+    }
+
+    #[test]
+    fn unknown_glyph() {
+        assert!(matches!(
+            parse_strict("c\nc#c"),
+            Err(BuildAssetError::UnknownGlyph {
+                glyph: '#',
+                line: 2,
+                column: 2
+            })
+        ));
+        assert!(matches!(
+            parse_strict("c\n~~~\n#"),
+            Err(BuildAssetError::UnknownGlyph {
+                glyph: '#',
+                line: 3,
+                column: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn ragged_row() {
+        assert!(matches!(
+            parse_strict("cc\nccc"),
+            Err(BuildAssetError::RaggedRow {
+                line: 2,
+                expected: 2,
+                found: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn orientation_rows() {
+        assert!(matches!(
+            parse_strict("p\nc\n~~~\n>"),
+            Err(BuildAssetError::OrientationRows {
+                line: 3,
+                column: 1,
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            parse_strict("pp\ncc\n~~~\n>\n."),
+            Err(BuildAssetError::OrientationRows {
+                line: 5,
+                column: 2,
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert!(parse_strict("p\nc\n~~~\n>\n.").is_ok());
+    }
+
+    #[test]
+    fn floating_item() {
+        let Err(BuildAssetError::FloatingItem { item, line, column }) =
+            parse_strict("..c\n...\n...\n...\nccc")
+        else {
+            panic!("Expected a floating item");
         };
-        Some(item)
+        assert_eq!((item.as_str(), line, column), ("Cube", 1, 3));
+    }
+
+    /// Item, position and orientation of every slot of `build`
+    fn slots(build: &ItemBuild) -> Vec<(GarbageItem, Vec3, ItemOrientation)> {
+        build
+            .items
+            .iter()
+            .map(|slot| (slot.item, slot.position, slot.orientation))
+            .collect()
+    }
+
+    const ORIENTED_BUILD: &str = "\
+o..
+|.c
+ppp
+~~~
+...
+-.v
+>>>
+---
+c|c
+ccc
+~~~
+.=.
+...
+";
+
+    #[test]
+    fn display_round_trip() {
+        let catalog = catalog();
+        let build = parse_strict(ORIENTED_BUILD).unwrap();
+        let displayed = build.display(&catalog).to_string();
+        let parsed = ItemBuild::parse_strict(displayed.as_bytes(), &catalog).unwrap();
+        assert_eq!(slots(&parsed), slots(&build));
+        assert!(slots(&build)
+            .iter()
+            .any(|(_, _, o)| *o == ItemOrientation::LyingTurned));
+        assert!(slots(&build).iter().any(|(_, p, _)| p.z > 0.0));
+    }
+
+    #[test]
+    fn saved_build_rests_on_the_ground() {
+        let catalog = catalog();
+        let build = parse_strict(ORIENTED_BUILD).unwrap();
+        let world = Transform::from_xyz(4.0, 0.0, -2.0).with_rotation(Quat::from_rotation_y(0.3));
+        let items: Vec<_> = build
+            .items
+            .iter()
+            .map(|slot| {
+                let transform = world
+                    * Transform::from_translation(slot.position * CELL_SIZE)
+                        .with_rotation(slot.orientation.rotation());
+                (slot.item, transform)
+            })
+            .collect();
+        // Saving from an origin below the items doesn't add empty rows
+        let origin = world.with_translation(world.translation - Vec3::Y * 3.0);
+        let saved = ItemBuild::from_items(origin, items, &catalog);
+        assert_eq!(slots(&saved), slots(&build));
+    }
+
+    #[test]
+    fn empty() {
+        assert!(matches!(parse_strict(""), Err(BuildAssetError::Empty)));
+        assert!(matches!(
+            parse_strict("...\n..."),
+            Err(BuildAssetError::Empty)
+        ));
     }
 }
@@ -1,4 +1,4 @@
-use super::{collector::OnCollectedFilterOut, Collector};
+use super::{collector::OnCollectedFilterOut, Collector, GarbageAssets, GarbageItem};
 use avian3d::prelude::*;
 use bevy::{
     ecs::component::{ComponentHooks, StorageType},
@@ -39,12 +39,24 @@ impl Component for Collected {
                     .map(|gtr| gtr.translation().xz())
                     .unwrap();
                 let dir = Dir2::new(collected_pos - collector_pos).ok();
+                // Mass properties are only computed during the physics prepare
+                // step, freshly spawned items fall back on their catalog mass
+                let mass = world
+                    .get::<Mass>(entity)
+                    .map(|m| m.0)
+                    .filter(|m| *m > 0.0)
+                    .or_else(|| {
+                        let item = world.get::<GarbageItem>(entity)?;
+                        let assets = world.get_resource::<GarbageAssets>()?;
+                        assets.catalog.get(*item).map(|def| def.mass())
+                    })
+                    .unwrap_or(0.0);
 
                 let Some(mut collector) = world.get_mut::<Collector>(collector_entity) else {
                     log::error!("Cannot find collector of `Collected` entity {entity:?}");
                     return;
                 };
-                if !collector.insert(entity, dir) {
+                if !collector.insert(entity, mass, dir) {
                     let mut commands = world.commands();
                     commands.entity(entity).remove::<Self>();
                     return;
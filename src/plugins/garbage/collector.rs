@@ -1,4 +1,6 @@
 use super::{
+    ballistic_velocity,
+    damage_drop::{DamageDrop, PickupImmunity},
     Collected, DistributionShape, GarbageBody, GarbageItem, PointDistribution, ThrownItem,
 };
 use crate::{GameState, ObjectLayer, ParticleConfig};
@@ -16,9 +18,15 @@ impl Plugin for CollectorPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Collector>()
             .register_type::<CollectorConfig>()
+            .register_type::<CollectorAttraction>()
             .add_systems(
                 FixedUpdate,
-                (auto_rotate, update_collected_position, collect_items)
+                (
+                    auto_rotate,
+                    update_collected_position,
+                    attract_items,
+                    collect_items,
+                )
                     .run_if(in_state(GameState::Running)),
             )
             .add_systems(PostUpdate, (update_radius, update_particles));
@@ -38,6 +46,23 @@ impl Plugin for CollectorPlugin {
 pub struct CollectorConfig {
     pub enabled: bool,
     pub color: Color,
+    /// Pulls uncollected items around the collector when enabled
+    pub attraction: Option<CollectorAttraction>,
+    /// Ejects collected items when the collector owner is damaged
+    pub damage_drop: Option<DamageDrop>,
+    /// Strength when competing with other collectors for items, defaults to
+    /// the collected items count
+    pub grip: Option<f32>,
+}
+
+/// Magnetic pull of uncollected items toward a [`Collector`]
+#[derive(Debug, Clone, Copy, Reflect)]
+pub struct CollectorAttraction {
+    /// Attraction range, beyond the collector radius
+    pub radius: f32,
+    /// Acceleration applied at the collector edge, falling off linearly to
+    /// zero at `radius`
+    pub strength: f32,
 }
 
 #[derive(Debug, Reflect)]
@@ -47,7 +72,65 @@ pub struct Collector {
     growing: bool,
     distribution: PointDistribution,
     shape: DistributionShape,
+    policy: ThrowPolicy,
     collected: Vec<Entity>,
+    /// Mass of each collected item, in the `collected` order
+    masses: Vec<f32>,
+    /// Maximum total mass of the collected items
+    max_mass: f32,
+    /// Cached world positions of the collected items, reused every update
+    #[reflect(ignore)]
+    positions: Vec<Vec3>,
+    /// Set when the distribution rotated since the positions were cached
+    #[reflect(ignore)]
+    stale_positions: bool,
+}
+
+/// Selects which collected item is thrown by [`Collector::throw_collected`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum ThrowPolicy {
+    /// The item closest to the aim direction
+    #[default]
+    ClosestToAim,
+    /// The item with the highest mass
+    Heaviest,
+    /// The item with the lowest mass
+    Lightest,
+    /// An item of the given kind, or the closest to the aim direction if there
+    /// is none
+    Kind(GarbageItem),
+}
+
+impl ThrowPolicy {
+    /// Returns the policy following this one, cycling through the collected
+    /// `kinds` which must be sorted and deduplicated
+    pub fn next(self, kinds: &[GarbageItem]) -> Self {
+        match self {
+            Self::ClosestToAim => Self::Heaviest,
+            Self::Heaviest => Self::Lightest,
+            Self::Lightest => kinds.first().map_or(Self::ClosestToAim, |k| Self::Kind(*k)),
+            Self::Kind(kind) => kinds
+                .iter()
+                .find(|k| **k > kind)
+                .map_or(Self::ClosestToAim, |k| Self::Kind(*k)),
+        }
+    }
+
+    /// Selects the item to throw among `candidates`, returns `None` for
+    /// [`Self::ClosestToAim`] or if no candidate matches
+    fn select(self, world: &World, mut candidates: impl Iterator<Item = Entity>) -> Option<Entity> {
+        let mass = |entity: &Entity| {
+            world
+                .get::<ColliderMassProperties>(*entity)
+                .map_or(0.0, |p| p.mass.0)
+        };
+        match self {
+            Self::ClosestToAim => None,
+            Self::Heaviest => candidates.max_by(|a, b| mass(a).total_cmp(&mass(b))),
+            Self::Lightest => candidates.min_by(|a, b| mass(a).total_cmp(&mass(b))),
+            Self::Kind(kind) => candidates.find(|e| world.get::<GarbageItem>(*e) == Some(&kind)),
+        }
+    }
 }
 
 #[derive(Debug, Component)]
@@ -106,6 +189,9 @@ impl CollectorBundle {
             config: CollectorConfig {
                 enabled: false,
                 color,
+                attraction: None,
+                damage_drop: None,
+                grip: None,
             },
             filter_out: OnCollectedFilterOut {
                 layer: on_collected_filter,
@@ -130,12 +216,37 @@ impl CollectorBundle {
             config: CollectorConfig {
                 enabled: false,
                 color,
+                attraction: None,
+                damage_drop: None,
+                grip: None,
             },
             filter_out: OnCollectedFilterOut {
                 layer: on_collected_filter,
             },
         }
     }
+
+    #[must_use]
+    pub const fn with_attraction(mut self, radius: f32, strength: f32) -> Self {
+        self.config.attraction = Some(CollectorAttraction { radius, strength });
+        self
+    }
+
+    #[must_use]
+    pub const fn with_max_mass(mut self, max_mass: f32) -> Self {
+        self.collector.set_max_mass(max_mass);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_damage_drop(mut self, ratio: f32, impulse: f32, immunity: f32) -> Self {
+        self.config.damage_drop = Some(DamageDrop {
+            ratio,
+            impulse,
+            immunity,
+        });
+        self
+    }
 }
 
 #[derive(Component, Debug, Reflect)]
@@ -182,7 +293,12 @@ impl Collector {
             growing: false,
             distribution,
             shape: DistributionShape::Circle,
+            policy: ThrowPolicy::ClosestToAim,
             collected: Vec::with_capacity(max_items),
+            masses: Vec::with_capacity(max_items),
+            max_mass: f32::INFINITY,
+            positions: Vec::with_capacity(max_items),
+            stale_positions: false,
         }
     }
 
@@ -192,13 +308,18 @@ impl Collector {
             growing: true,
             distribution: PointDistribution::new(min_radius, max_distance),
             shape: DistributionShape::Circle,
+            policy: ThrowPolicy::ClosestToAim,
             collected: Vec::with_capacity(max_items),
+            masses: Vec::with_capacity(max_items),
+            max_mass: f32::INFINITY,
+            positions: Vec::with_capacity(max_items),
+            stale_positions: false,
         }
     }
 
     pub fn radius(&self) -> f32 {
         if self.growing {
-            self.distribution.radius(self.len())
+            self.distribution.radius(self.len(), self.shape)
         } else {
             self.min_radius
         }
@@ -221,22 +342,63 @@ impl Collector {
     }
 
     #[inline]
-    pub fn len(&self) -> usize {
+    pub const fn len(&self) -> usize {
         self.collected.len()
     }
 
     #[inline]
-    pub fn points_len(&self) -> usize {
+    pub const fn points_len(&self) -> usize {
         self.distribution.len()
     }
 
+    /// Radius of the collector sensor
+    pub fn sensor_radius(&self) -> f32 {
+        self.radius() * Self::COLLECTOR_RADIUS_COEF
+    }
+
+    #[inline]
+    pub const fn is_full(&self) -> bool {
+        self.len() >= self.collected.capacity()
+    }
+
+    /// Total mass of the collected items
+    pub fn mass(&self) -> f32 {
+        self.masses.iter().sum()
+    }
+
+    #[inline]
+    pub const fn max_mass(&self) -> f32 {
+        self.max_mass
+    }
+
+    pub const fn set_max_mass(&mut self, max_mass: f32) {
+        self.max_mass = max_mass;
+    }
+
+    /// Returns `true` if an item of `mass` fits in both the item count and
+    /// mass budgets
+    pub fn can_collect(&self, mass: f32) -> bool {
+        !self.is_full() && self.mass() + mass <= self.max_mass
+    }
+
     #[inline]
-    pub fn is_empty(&self) -> bool {
+    pub const fn is_empty(&self) -> bool {
         self.collected.is_empty()
     }
 
-    pub fn insert(&mut self, entity: Entity, dir: Option<Dir2>) -> bool {
-        if self.len() >= self.collected.capacity() {
+    #[inline]
+    pub fn collected(&self) -> &[Entity] {
+        &self.collected
+    }
+
+    /// Target positions of the collected items, in the `collected` order
+    #[inline]
+    pub fn positions(&self) -> &[Vec3] {
+        &self.positions
+    }
+
+    pub fn insert(&mut self, entity: Entity, mass: f32, dir: Option<Dir2>) -> bool {
+        if !self.can_collect(mass) {
             return false;
         }
         match (
@@ -244,10 +406,13 @@ impl Collector {
             dir.and_then(|d| self.distribution.find_closest_aligned_point(d)),
         ) {
             (true, Some((index, _))) => {
+                // Neighbours shift by one slot, keeping the item order
                 self.collected.insert(index, entity);
+                self.masses.insert(index, mass);
             }
             _ => {
                 self.collected.push(entity);
+                self.masses.push(mass);
             }
         }
         if self.growing {
@@ -259,12 +424,54 @@ impl Collector {
     pub fn remove(&mut self, entity: Entity) -> Option<Entity> {
         let index = self.collected.iter().position(|e| *e == entity)?;
         let res = self.collected.remove(index);
+        self.masses.remove(index);
         if self.growing {
             self.distribution.update(self.len(), self.shape);
         }
         Some(res)
     }
 
+    /// Rotates the distribution over `dt` seconds, or orients it towards
+    /// `forward` for directional shapes
+    pub fn update_rotation(&mut self, forward: Dir2, dt: f32) {
+        let angle = self.distribution.angle();
+        match self.shape {
+            DistributionShape::Circle
+            | DistributionShape::Spiral
+            | DistributionShape::Rings
+            | DistributionShape::Sphere => {
+                let radius = self.radius();
+                self.distribution.rotate(Self::rotation_angle(radius, dt));
+            }
+            DistributionShape::Arc | DistributionShape::Line => {
+                self.distribution.set_direction(forward);
+            }
+        }
+        self.stale_positions |= self.distribution.angle() != angle;
+    }
+
+    /// Updates the cached target positions of the collected items, around
+    /// `center` or along the `body`
+    pub fn update_positions(&mut self, center: Vec3, body: Option<&GarbageBody>) {
+        self.stale_positions = false;
+        self.positions.clear();
+        match body {
+            Some(b) => {
+                b.extend_3d_positions(
+                    self.collected.len(),
+                    &self.distribution,
+                    &mut self.positions,
+                );
+            }
+            None => self.positions.extend(
+                self.distribution
+                    .points()
+                    .iter()
+                    .map(|p| center + Vec3::Y * 0.5 + *p),
+            ),
+        }
+    }
+
     pub fn set_shape(&mut self, shape: DistributionShape) {
         log::info!("Collector shape is now a `{shape}`");
         self.shape = shape;
@@ -281,37 +488,130 @@ impl Collector {
         self.shape
     }
 
+    pub const fn set_policy(&mut self, policy: ThrowPolicy) {
+        self.policy = policy;
+    }
+
+    #[inline]
+    pub const fn policy(&self) -> ThrowPolicy {
+        self.policy
+    }
+
+    /// Throws a collected item, selected by the collector [`ThrowPolicy`]
     pub fn throw_collected(&self, direction: Dir2, force: f32) -> Option<impl FnOnce(&mut World)> {
-        let (index, _) = self.distribution.find_closest_aligned_point(direction)?;
-        let Some(entity) = self.collected.get(index).copied() else {
-            log::error!(
-                "Collector and circular distribution are out of sync, No entity found at index \
-                 {index:?}"
-            );
-            return None;
-        };
-        let direction = Vec3::new(direction.x, 0.0, direction.y);
+        self.throw_salvo(&[direction], force)
+    }
+
+    /// Throws a different collected item in each of the `directions` at once,
+    /// selected by the collector [`ThrowPolicy`]
+    pub fn throw_salvo(&self, directions: &[Dir2], force: f32) -> Option<impl FnOnce(&mut World)> {
+        let mut select = self.item_selector()?;
+        let directions = directions.to_vec();
+        Some(move |world: &mut World| {
+            for direction in directions {
+                let Some(entity) = select(world, direction) else {
+                    break;
+                };
+                throw_item(world, entity, direction, force);
+            }
+        })
+    }
+
+    /// Throws a collected item in an arc landing on `target` after
+    /// `flight_time` seconds, selected by the collector [`ThrowPolicy`]
+    pub fn throw_lobbed(
+        &self,
+        target: Vec3,
+        direction: Dir2,
+        flight_time: f32,
+    ) -> Option<impl FnOnce(&mut World)> {
+        let mut select = self.item_selector()?;
         Some(move |world: &mut World| {
-            let mass = world
-                .get::<ColliderMassProperties>(entity)
-                .map(|p| p.mass.0)
-                .unwrap_or(1.0);
-            if let Some(collected) = world.get::<Collected>(entity) {
-                let collector_entity = collected.collector_entity;
-                let mut entity_cmd = world.entity_mut(entity);
-                entity_cmd.remove::<Collected>().insert((
-                    LinearVelocity::default(),
-                    ExternalImpulse::new(direction * force * mass),
-                    ThrownItem::new(collector_entity),
-                ));
+            if let Some(entity) = select(world, direction) {
+                lob_item(world, entity, target, flight_time);
+            }
+        })
+    }
+
+    /// Returns a function selecting a distinct collected item to throw in the
+    /// given direction on each call
+    fn item_selector(&self) -> Option<impl FnMut(&World, Dir2) -> Option<Entity>> {
+        let first = *self.collected.first()?;
+        let policy = self.policy;
+        let mut collector_entity = None;
+        let mut taken = Vec::new();
+        Some(move |world: &World, direction: Dir2| {
+            // The collector is found once through a collected item, as thrown items
+            // leave it
+            if collector_entity.is_none() {
+                collector_entity = world.get::<Collected>(first).map(|c| c.collector_entity);
             }
+            let collector = world.get::<Self>(collector_entity?)?;
+            if collector.is_empty() {
+                return None;
+            }
+            let available = |entity: &Entity| !taken.contains(entity);
+            let Some(entity) = policy
+                .select(world, collector.collected.iter().copied().filter(available))
+                .or_else(|| {
+                    let index = collector
+                        .distribution
+                        .closest_available_point(direction, |i| {
+                            collector.collected.get(i).is_some_and(available)
+                        })?;
+                    Some(collector.collected[index])
+                })
+            else {
+                log::error!("Collector and distribution are out of sync, No entity to throw");
+                return None;
+            };
+            taken.push(entity);
+            Some(entity)
         })
     }
 }
 
+fn throw_item(world: &mut World, entity: Entity, direction: Dir2, force: f32) {
+    let direction = Vec3::new(direction.x, 0.0, direction.y);
+    let mass = world
+        .get::<ColliderMassProperties>(entity)
+        .map(|p| p.mass.0)
+        .unwrap_or(1.0);
+    if let Some(collected) = world.get::<Collected>(entity) {
+        let collector_entity = collected.collector_entity;
+        let mut entity_cmd = world.entity_mut(entity);
+        entity_cmd.remove::<Collected>().insert((
+            LinearVelocity::default(),
+            ExternalImpulse::new(direction * force * mass),
+            ThrownItem::new(collector_entity),
+        ));
+    }
+}
+
+fn lob_item(world: &mut World, entity: Entity, target: Vec3, flight_time: f32) {
+    let Some(from) = world
+        .get::<GlobalTransform>(entity)
+        .map(GlobalTransform::translation)
+    else {
+        return;
+    };
+    let gravity = world
+        .get_resource::<Gravity>()
+        .map_or_else(|| Gravity::default().0, |g| g.0);
+    let damping = world.get::<LinearDamping>(entity).map_or(0.0, |d| d.0);
+    let velocity = ballistic_velocity(from, target, flight_time, gravity, damping);
+    if let Some(collected) = world.get::<Collected>(entity) {
+        let collector_entity = collected.collector_entity;
+        let mut entity_cmd = world.entity_mut(entity);
+        entity_cmd
+            .remove::<Collected>()
+            .insert((LinearVelocity(velocity), ThrownItem::new(collector_entity)));
+    }
+}
+
 pub fn update_radius(mut collectors: Query<(&mut Transform, &Collector), Changed<Collector>>) {
     for (mut tr, collector) in &mut collectors {
-        tr.scale = Vec3::splat(collector.radius() * Collector::COLLECTOR_RADIUS_COEF);
+        tr.scale = Vec3::splat(collector.sensor_radius());
     }
 }
 
@@ -345,24 +645,26 @@ fn update_particles(
 
 fn update_collected_position(
     mut collected: Query<(&Transform, &mut LinearVelocity), (With<Collected>, Without<ThrownItem>)>,
-    collectors: Query<(&GlobalTransform, &Collector, Option<&GarbageBody>)>,
+    mut collectors: Query<(
+        Ref<GlobalTransform>,
+        &mut Collector,
+        Option<Ref<GarbageBody>>,
+    )>,
 ) {
-    for (center_tr, collector, body) in &collectors {
-        let center = center_tr.translation();
-        let mut collected = collected.iter_many_mut(&collector.collected);
-        let positions = match body {
-            Some(b) => b.compute_3d_positions(collector.len(), &collector.distribution),
-            None => collector
-                .distribution
-                .points()
-                .iter()
-                .map(|p| center + Vec3::new(p.x, 0.5, p.y))
-                .collect(),
-        };
+    for (center_tr, mut collector, body) in &mut collectors {
+        let moved = center_tr.is_changed() || body.as_ref().is_some_and(DetectChanges::is_changed);
+        if moved || collector.is_changed() || collector.stale_positions {
+            // The positions are only a cache, updating them doesn't mark the
+            // collector as changed
+            collector
+                .bypass_change_detection()
+                .update_positions(center_tr.translation(), body.as_deref());
+        }
+        let mut collected = collected.iter_many_mut(collector.collected.iter());
         let mut i = 0_usize;
         while let Some((tr, mut linvel)) = collected.fetch_next() {
-            let Some(target) = positions.get(i).copied() else {
-                log::error!("Collector has more positions than it has collected items");
+            let Some(target) = collector.positions.get(i).copied() else {
+                log::error!("Collector has fewer positions than it has collected items");
                 continue;
             };
             let delta = (target - tr.translation) * Collector::COLLECTED_SPEED;
@@ -376,19 +678,50 @@ fn update_collected_position(
 fn auto_rotate(time: Res<Time>, mut collectors: Query<(&GlobalTransform, &mut Collector)>) {
     let dt = time.delta_seconds();
     for (gtr, mut collector) in &mut collectors {
-        match collector.shape {
-            DistributionShape::Circle => {
-                let radius = collector.radius();
-                collector
-                    .distribution
-                    .rotate(Collector::rotation_angle(radius, dt));
+        let forward = Dir2::new_unchecked(gtr.forward().xz());
+        // Rotating doesn't change the collected items, it only invalidates the
+        // cached positions
+        collector
+            .bypass_change_detection()
+            .update_rotation(forward, dt);
+    }
+}
+
+fn attract_items(
+    time: Res<Time>,
+    collectors: Query<(Entity, &GlobalTransform, &Collector, &CollectorConfig)>,
+    mut items: Query<
+        (
+            &GlobalTransform,
+            &RigidBody,
+            &mut LinearVelocity,
+            Option<&PickupImmunity>,
+        ),
+        (With<GarbageItem>, Without<Collected>, Without<ThrownItem>),
+    >,
+) {
+    let dt = time.delta_seconds();
+    for (collector_entity, center_tr, collector, config) in &collectors {
+        let Some(attraction) = config.attraction.filter(|_| config.enabled) else {
+            continue;
+        };
+        let center = center_tr.translation();
+        // Items reaching the sensor are collected by `collect_items`
+        if collector.is_full() {
+            continue;
+        }
+        let edge = collector.sensor_radius();
+        for (gtr, body, mut linvel, immunity) in &mut items {
+            if !body.is_dynamic() || !PickupImmunity::allows(immunity, collector_entity) {
+                continue;
             }
-            DistributionShape::Arc => {
-                let dir = gtr.forward().xz();
-                collector
-                    .distribution
-                    .set_direction(Dir2::new_unchecked(dir));
+            let delta = center - gtr.translation();
+            let distance = delta.length();
+            if distance <= edge || distance >= edge + attraction.radius {
+                continue;
             }
+            let falloff = 1.0 - (distance - edge) / attraction.radius;
+            linvel.0 += delta / distance * attraction.strength * falloff * dt;
         }
     }
 }
@@ -396,40 +729,34 @@ fn auto_rotate(time: Res<Time>, mut collectors: Query<(&GlobalTransform, &mut Co
 fn collect_items(
     mut commands: Commands,
     collectors: Query<(Entity, &CollidingEntities, &CollectorConfig), With<Collector>>,
-    items: Query<Entity, (With<GarbageItem>, Without<Collected>, Without<ThrownItem>)>,
+    items: Query<
+        (Entity, Option<&PickupImmunity>),
+        (With<GarbageItem>, Without<Collected>, Without<ThrownItem>),
+    >,
 ) {
     for (collector_entity, collision, config) in &collectors {
         if !config.enabled {
             continue;
         }
-        for item in items.iter_many(&collision.0) {
+        for (item, immunity) in items.iter_many(&collision.0) {
+            if !PickupImmunity::allows(immunity, collector_entity) {
+                continue;
+            }
             commands.entity(item).insert(Collected { collector_entity });
         }
     }
 }
 
 #[cfg(feature = "debug")]
-fn draw_gizmos(
-    mut gizmos: Gizmos,
-    collectors: Query<(&GlobalTransform, &Collector, Option<&GarbageBody>)>,
-) {
+fn draw_gizmos(mut gizmos: Gizmos, collectors: Query<(&GlobalTransform, &Collector)>) {
     use bevy::color::palettes::css::DARK_GRAY;
     let color = Color::Srgba(DARK_GRAY);
 
-    for (gt, collector, body) in &collectors {
+    for (gt, collector) in &collectors {
         let translation = gt.translation();
         gizmos.circle(translation, Dir3::Y, collector.radius(), color);
-        let positions = match body {
-            Some(b) => b.compute_3d_positions(collector.len(), &collector.distribution),
-            None => collector
-                .distribution
-                .points()
-                .iter()
-                .map(|p| translation + Vec3::new(p.x, 1.0, p.y))
-                .collect(),
-        };
-        for pos in positions {
-            gizmos.sphere(pos, Quat::IDENTITY, 0.2, color);
+        for pos in collector.positions() {
+            gizmos.sphere(*pos, Quat::IDENTITY, 0.2, color);
         }
     }
 }
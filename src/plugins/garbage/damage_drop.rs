@@ -0,0 +1,95 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, thread_rng};
+
+use crate::{add_impulse, DamageDealt};
+
+use super::{Collected, Collector, CollectorConfig};
+
+pub struct DamageDropPlugin;
+
+impl Plugin for DamageDropPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<DamageDrop>()
+            .register_type::<PickupImmunity>()
+            .add_systems(Update, (drop_on_damage, tick_pickup_immunity));
+    }
+}
+
+/// Ejects part of the collected items when the collector owner is damaged
+#[derive(Debug, Clone, Copy, Reflect)]
+pub struct DamageDrop {
+    /// Fraction of the collected items ejected on each hit
+    pub ratio: f32,
+    /// Radial impulse applied to ejected items
+    pub impulse: f32,
+    /// Duration in seconds during which ejected items cannot be collected back
+    /// by the same collector
+    pub immunity: f32,
+}
+
+/// Prevents `collector_entity` from collecting the item until the timer ends
+#[derive(Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct PickupImmunity {
+    pub collector_entity: Entity,
+    timer: f32,
+}
+
+impl PickupImmunity {
+    /// Returns `true` if `collector_entity` can collect the item
+    pub fn allows(immunity: Option<&Self>, collector_entity: Entity) -> bool {
+        immunity.map(|i| i.collector_entity) != Some(collector_entity)
+    }
+}
+
+fn drop_on_damage(
+    mut commands: Commands,
+    mut events: EventReader<DamageDealt>,
+    targets: Query<&Children>,
+    collectors: Query<(Entity, &Collector, &CollectorConfig, &GlobalTransform)>,
+    items: Query<(&GlobalTransform, &ColliderMassProperties), With<Collected>>,
+) {
+    let mut rng = thread_rng();
+    for event in events.read() {
+        let Ok(children) = targets.get(event.target) else {
+            continue;
+        };
+        for (collector_entity, collector, config, center) in collectors.iter_many(children) {
+            let Some(drop) = config.damage_drop else {
+                continue;
+            };
+            let amount = (collector.len() as f32 * drop.ratio).ceil() as usize;
+            let center = center.translation();
+            for entity in collector.collected().choose_multiple(&mut rng, amount) {
+                let Ok((gtr, mass)) = items.get(*entity) else {
+                    continue;
+                };
+                let outward = (gtr.translation() - center).with_y(0.0);
+                let direction = (outward.normalize_or_zero() + Vec3::Y).normalize();
+                commands
+                    .entity(*entity)
+                    .remove::<Collected>()
+                    .insert(PickupImmunity {
+                        collector_entity,
+                        timer: drop.immunity,
+                    });
+                commands.add(add_impulse(*entity, direction * drop.impulse * mass.mass.0));
+            }
+        }
+    }
+}
+
+fn tick_pickup_immunity(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut items: Query<(Entity, &mut PickupImmunity)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut immunity) in &mut items {
+        immunity.timer -= dt;
+        if immunity.timer <= 0.0 {
+            commands.entity(entity).remove::<PickupImmunity>();
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use std::f32::consts::{FRAC_PI_4, TAU};
+use std::f32::consts::{FRAC_PI_4, PI, TAU};
 use strum::Display;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Default, Display)]
@@ -8,6 +8,14 @@ pub enum DistributionShape {
     #[default]
     Circle,
     Arc,
+    /// Straight wall perpendicular to the aim direction
+    Line,
+    /// Archimedean spiral with turns separated by the points distance
+    Spiral,
+    /// Concentric rings, inner rings are filled first
+    Rings,
+    /// 3D orbit sphere around the center
+    Sphere,
 }
 
 /// Computes the ideal circle radius for `amount` of points separated by
@@ -30,26 +38,56 @@ fn radius(min_radius: f32, max_distance: f32, amount: usize) -> f32 {
     calculated_radius.max(min_radius)
 }
 
-/// Rotates a given point by a specified angle
+/// Computes the amount of points separated by at most `max_distance` on a
+/// ring of the given `radius`
+fn ring_capacity(radius: f32, max_distance: f32) -> usize {
+    ((TAU * radius / max_distance).ceil() as usize).max(1)
+}
+
+/// Computes the radius of a sphere on which `amount` of points are separated
+/// by roughly `max_distance`
+fn sphere_radius(min_radius: f32, max_distance: f32, amount: usize) -> f32 {
+    // Fibonacci sphere points are packed close to a hexagonal grid, a square of
+    // `max_distance` side per point keeps neighbours within `max_distance`
+    let area = amount as f32 * max_distance * max_distance;
+    (area / (4.0 * PI)).sqrt().max(min_radius)
+}
+
+/// Computes the alignment of `point` with `direction` on the XZ plane, as the
+/// cosine of the angle between them. Unlike an angle difference it doesn't
+/// need to be wrapped across the `±PI` boundary
+fn alignment(point: &Vec3, direction: Dir2) -> f32 {
+    Vec2::new(point.x, point.z)
+        .normalize_or_zero()
+        .dot(*direction)
+}
+
+/// Builds a point on the XZ plane at `angle` and `radius` from the center
+fn planar_point(radius: f32, angle: f32) -> Vec3 {
+    Vec3::new(radius * angle.cos(), 0.0, radius * angle.sin())
+}
+
+/// Rotates a given point around the Y axis by a specified angle
 ///
 /// # Arguments
 ///
 /// * `point` - The point to be rotated.
-/// * `angle` - The angle to rotate the point by.
+/// * `(sin, cos)` - The sine and cosine of the angle to rotate the point by.
 ///
 /// # Returns
 ///
-/// * The rotated point as a `Vec2`.
-fn rotated_point(point: &Vec2, angle: f32) -> Vec2 {
-    let rotated_x = point.x.mul_add(angle.cos(), -point.y * angle.sin());
-    let rotated_y = point.x.mul_add(angle.sin(), point.y * angle.cos());
-    Vec2::new(rotated_x, rotated_y)
+/// * The rotated point as a `Vec3`.
+fn rotated_point(point: &Vec3, (sin, cos): (f32, f32)) -> Vec3 {
+    let rotated_x = point.x.mul_add(cos, -point.z * sin);
+    let rotated_z = point.x.mul_add(sin, point.z * cos);
+    Vec3::new(rotated_x, point.y, rotated_z)
 }
 
 /// A component for managing circular distributions of points.
 ///
 /// This struct allows for the generation and retrieval of points distributed
-/// evenly in an oriented circle.
+/// evenly in an oriented [`DistributionShape`]. Points are relative to the
+/// center, on the XZ plane except for [`DistributionShape::Sphere`].
 #[derive(Debug, Reflect)]
 pub struct PointDistribution {
     /// The minimum radius of the circle .
@@ -58,8 +96,10 @@ pub struct PointDistribution {
     pub max_distance: f32,
     /// Cached angle for oriented rotation
     current_angle: f32,
+    /// Shape of the cached points
+    shape: DistributionShape,
     /// Cached points for the distribution.
-    points: Vec<Vec2>,
+    points: Vec<Vec3>,
 }
 
 impl PointDistribution {
@@ -72,77 +112,210 @@ impl PointDistribution {
             min_radius,
             max_distance,
             current_angle: 0.0,
+            shape: DistributionShape::Circle,
             points: Vec::new(),
         }
     }
 
-    pub fn radius(&self, amount: usize) -> f32 {
-        radius(self.min_radius, self.max_distance, amount)
+    /// Computes the distance from the center to the farthest point of the
+    /// `shape` distribution of `amount` points
+    pub fn radius(&self, amount: usize, shape: DistributionShape) -> f32 {
+        match shape {
+            DistributionShape::Circle | DistributionShape::Arc => {
+                radius(self.min_radius, self.max_distance, amount)
+            }
+            DistributionShape::Line => {
+                let half = amount.saturating_sub(1) as f32 * self.max_distance / 2.0;
+                Vec2::new(self.inner_radius(), half).length()
+            }
+            DistributionShape::Spiral => self
+                .spiral(amount)
+                .last()
+                .map_or_else(|| self.inner_radius(), |(_, radius)| radius),
+            DistributionShape::Rings => {
+                let mut radius = self.inner_radius();
+                let mut remaining = amount;
+                loop {
+                    let capacity = ring_capacity(radius, self.max_distance);
+                    if remaining <= capacity {
+                        break radius;
+                    }
+                    remaining -= capacity;
+                    radius += self.max_distance;
+                }
+            }
+            DistributionShape::Sphere => sphere_radius(self.min_radius, self.max_distance, amount),
+        }
+    }
+
+    /// Radius of the innermost points of the distribution, avoiding points on
+    /// the center
+    const fn inner_radius(&self) -> f32 {
+        self.min_radius.max(self.max_distance)
+    }
+
+    /// Iterates over the angle and radius of `amount` points of a spiral
+    /// separated by `max_distance`
+    fn spiral(&self, amount: usize) -> impl Iterator<Item = (f32, f32)> + '_ {
+        let growth = self.max_distance / TAU;
+        let mut angle = 0.0_f32;
+        (0..amount).map(move |_| {
+            let radius = growth.mul_add(angle, self.inner_radius());
+            let res = (angle, radius);
+            // The spiral length over this step is at most `max_distance`, bounding
+            // its radius by the radius after a full radian
+            let outer = radius + growth;
+            angle += self.max_distance / outer.hypot(growth);
+            res
+        })
     }
 
     #[inline]
-    pub fn len(&self) -> usize {
+    pub const fn len(&self) -> usize {
         self.points.len()
     }
 
     #[inline]
-    pub fn is_empty(&self) -> bool {
+    pub const fn is_empty(&self) -> bool {
         self.points.is_empty()
     }
 
+    /// Iterates over the start index, radius and point count of the rings of
+    /// a [`DistributionShape::Rings`] distribution of `amount` points
+    fn rings(&self, amount: usize) -> impl Iterator<Item = (usize, f32, usize)> + '_ {
+        let mut radius = self.inner_radius();
+        let mut start = 0;
+        std::iter::from_fn(move || {
+            if start >= amount {
+                return None;
+            }
+            let count = ring_capacity(radius, self.max_distance).min(amount - start);
+            let res = (start, radius, count);
+            start += count;
+            radius += self.max_distance;
+            Some(res)
+        })
+    }
+
+    /// Returns the index of the first cached point which changes when the
+    /// amount of points goes from the current one to `amount`, points before
+    /// it are kept as is
+    fn first_changed(&self, amount: usize) -> usize {
+        let kept = self.points.len().min(amount);
+        match self.shape {
+            // Points only depend on their index
+            DistributionShape::Arc | DistributionShape::Spiral | DistributionShape::Rings => kept,
+            // Every point depends on the total amount
+            DistributionShape::Circle | DistributionShape::Line | DistributionShape::Sphere => 0,
+        }
+    }
+
     /// Updates the amount of points in the distribution, effectively updating
-    /// the points caches
+    /// the points caches. Only the points affected by the change are computed
+    /// again.
     ///
     /// # Arguments
     ///
     /// * `amount` - The number of points to be distributed.
     /// * `shape` - The new distribution shape
     pub fn update(&mut self, amount: usize, shape: DistributionShape) {
-        self.points = match shape {
+        let start = if shape == self.shape {
+            self.first_changed(amount)
+        } else {
+            0
+        };
+        // The points buffer is reused to avoid reallocating on every change
+        let mut points = std::mem::take(&mut self.points);
+        points.truncate(start);
+        self.shape = shape;
+        match shape {
             DistributionShape::Arc => {
                 let mut radius = self.min_radius;
                 let mut offset: usize = 0;
-                (0..amount)
-                    .map(|i| {
-                        let theta = self.max_distance / radius;
-                        let inner = i - offset;
-                        let mut angle_offset = inner as f32 * theta;
-                        if angle_offset > FRAC_PI_4 {
-                            offset = i;
-                            radius += self.max_distance;
-                            angle_offset = 0.0;
-                        }
-                        let step = if i % 2 == 0 {
-                            self.current_angle + angle_offset
-                        } else {
-                            self.current_angle - angle_offset
-                        };
-                        let x = radius * step.cos();
-                        let y = radius * step.sin();
-                        Vec2::new(x, y)
-                    })
-                    .collect()
+                points.extend(
+                    (0..amount)
+                        .map(|i| {
+                            let theta = self.max_distance / radius;
+                            let inner = i - offset;
+                            let mut angle_offset = inner as f32 * theta;
+                            if angle_offset > FRAC_PI_4 {
+                                offset = i;
+                                radius += self.max_distance;
+                                angle_offset = 0.0;
+                            }
+                            (i, radius, angle_offset)
+                        })
+                        .skip(start)
+                        .map(|(i, radius, angle_offset)| {
+                            let step = if i % 2 == 0 {
+                                self.current_angle + angle_offset
+                            } else {
+                                self.current_angle - angle_offset
+                            };
+                            planar_point(radius, step)
+                        }),
+                );
             }
             DistributionShape::Circle => {
-                let radius = self.radius(amount);
+                let radius = self.radius(amount, shape);
                 let theta = TAU / (amount as f32);
-                (0..amount)
-                    .map(|i| {
+                points.extend((0..amount).map(|i| {
+                    let step = theta.mul_add(i as f32, self.current_angle);
+                    planar_point(radius, step)
+                }));
+            }
+            DistributionShape::Line => {
+                let front = self.inner_radius();
+                let half = amount.saturating_sub(1) as f32 * self.max_distance / 2.0;
+                let (sin, cos) = self.current_angle.sin_cos();
+                points.extend((0..amount).map(|i| {
+                    let lateral = (i as f32).mul_add(self.max_distance, -half);
+                    Vec3::new(
+                        cos.mul_add(front, -sin * lateral),
+                        0.0,
+                        sin.mul_add(front, cos * lateral),
+                    )
+                }));
+            }
+            DistributionShape::Spiral => points.extend(
+                self.spiral(amount)
+                    .skip(start)
+                    .map(|(angle, radius)| planar_point(radius, angle + self.current_angle)),
+            ),
+            DistributionShape::Rings => {
+                for (ring_start, radius, count) in self.rings(amount) {
+                    if ring_start + count <= start {
+                        continue;
+                    }
+                    // The outermost ring is filled progressively, keeping the spacing
+                    let theta = TAU / ring_capacity(radius, self.max_distance) as f32;
+                    points.extend((start.saturating_sub(ring_start)..count).map(|i| {
                         let step = theta.mul_add(i as f32, self.current_angle);
-                        let x = radius * step.cos();
-                        let y = radius * step.sin();
-                        Vec2::new(x, y)
-                    })
-                    .collect()
+                        planar_point(radius, step)
+                    }));
+                }
             }
-        };
+            DistributionShape::Sphere => {
+                let radius = sphere_radius(self.min_radius, self.max_distance, amount);
+                // Fibonacci sphere, with the golden angle between each point
+                let golden_angle = PI * (3.0 - 5.0_f32.sqrt());
+                points.extend((0..amount).map(|i| {
+                    let y = 1.0 - 2.0 * (i as f32 + 0.5) / amount as f32;
+                    let ring = y.mul_add(-y, 1.0).sqrt();
+                    let step = golden_angle.mul_add(i as f32, self.current_angle);
+                    planar_point(ring, step).with_y(y) * radius
+                }));
+            }
+        }
+        self.points = points;
     }
 
     pub fn rotate(&mut self, angle: f32) {
         self.current_angle = (self.current_angle + angle) % TAU;
+        let sin_cos = angle.sin_cos();
         self.points
             .iter_mut()
-            .for_each(|point| *point = rotated_point(point, angle));
+            .for_each(|point| *point = rotated_point(point, sin_cos));
     }
 
     pub fn set_direction(&mut self, direction: Dir2) {
@@ -151,8 +324,14 @@ impl PointDistribution {
         self.rotate(angle_diff);
     }
 
+    /// Current rotation of the distribution, in radians
+    #[inline]
+    pub const fn angle(&self) -> f32 {
+        self.current_angle
+    }
+
     /// Retrieves the points
-    pub fn points(&self) -> &[Vec2] {
+    pub fn points(&self) -> &[Vec3] {
         &self.points
     }
 
@@ -166,23 +345,127 @@ impl PointDistribution {
     /// # Returns
     ///
     /// * The closest point index and value.
-    pub fn find_closest_aligned_point(&self, direction: Dir2) -> Option<(usize, Vec2)> {
-        // Convert direction to an angle
-        let target_angle = direction.y.atan2(direction.x);
-
-        let mut index = 0;
-        let mut current_diff: f32 = f32::MAX;
-        // Find the point with the minimum angular difference
-        for (i, point) in self.points.iter().enumerate() {
-            let angle = point.y.atan2(point.x);
-            let diff = (target_angle - angle).abs();
-
-            if diff < current_diff {
-                index = i;
-                current_diff = diff;
+    pub fn find_closest_aligned_point(&self, direction: Dir2) -> Option<(usize, Vec3)> {
+        if self.points.is_empty() {
+            return None;
+        }
+        let index = if self.shape == DistributionShape::Circle {
+            // Points are evenly spaced from `current_angle`, the index is direct
+            let amount = self.points.len();
+            let theta = TAU / amount as f32;
+            let angle = direction.y.atan2(direction.x) - self.current_angle;
+            ((angle / theta).round() as isize).rem_euclid(amount as isize) as usize
+        } else {
+            // Find the point with the best alignment
+            self.points
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| {
+                    alignment(a, direction).total_cmp(&alignment(b, direction))
+                })
+                .map_or(0, |(i, _)| i)
+        };
+        self.points.get(index).map(|p| (index, *p))
+    }
+
+    /// Finds the index of the point best aligned with `direction` among the
+    /// `available` ones
+    pub fn closest_available_point(
+        &self,
+        direction: Dir2,
+        available: impl Fn(usize) -> bool,
+    ) -> Option<usize> {
+        self.points
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| available(*i))
+            .max_by(|(_, a), (_, b)| alignment(a, direction).total_cmp(&alignment(b, direction)))
+            .map(|(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_DISTANCE: f32 = 1.5;
+    const SHAPES: [DistributionShape; 6] = [
+        DistributionShape::Circle,
+        DistributionShape::Arc,
+        DistributionShape::Line,
+        DistributionShape::Spiral,
+        DistributionShape::Rings,
+        DistributionShape::Sphere,
+    ];
+
+    /// Distance from each point to its closest neighbour
+    fn neighbour_distances(points: &[Vec3]) -> Vec<f32> {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                points
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, b)| a.distance(*b))
+                    .fold(f32::INFINITY, f32::min)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn point_counts() {
+        for shape in SHAPES {
+            let mut distribution = PointDistribution::new(2.0, MAX_DISTANCE);
+            for amount in [0, 1, 2, 37, 200] {
+                distribution.update(amount, shape);
+                assert_eq!(distribution.len(), amount, "{shape} with {amount} points");
+                assert!(distribution.points().iter().all(|p| p.is_finite()));
             }
         }
+    }
 
-        self.points.get(index).map(|p| (index, *p))
+    #[test]
+    fn neighbour_spacing() {
+        for shape in [
+            DistributionShape::Line,
+            DistributionShape::Spiral,
+            DistributionShape::Rings,
+            DistributionShape::Sphere,
+        ] {
+            for amount in [2, 3, 10, 37, 100, 200] {
+                let mut distribution = PointDistribution::new(0.0, MAX_DISTANCE);
+                distribution.update(amount, shape);
+                let distances = neighbour_distances(distribution.points());
+                let max = distances.iter().copied().fold(0.0, f32::max);
+                let min = distances.iter().copied().fold(f32::INFINITY, f32::min);
+                assert!(
+                    max <= MAX_DISTANCE + 1e-3,
+                    "{shape} with {amount} points has a spacing of {max}"
+                );
+                assert!(
+                    min >= MAX_DISTANCE * 0.5,
+                    "{shape} with {amount} points has overlapping points at {min}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn incremental_update_matches_full_update() {
+        for shape in SHAPES {
+            let mut incremental = PointDistribution::new(2.0, MAX_DISTANCE);
+            incremental.update(0, shape);
+            for amount in (0..60).chain((0..60).rev()) {
+                incremental.update(amount, shape);
+                let mut full = PointDistribution::new(2.0, MAX_DISTANCE);
+                full.update(amount, shape);
+                for (a, b) in incremental.points().iter().zip(full.points()) {
+                    assert!(a.distance(*b) < 1e-4, "{shape} with {amount} points");
+                }
+                assert_eq!(incremental.len(), full.len());
+            }
+        }
     }
 }
@@ -0,0 +1,72 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::Dead;
+
+use super::{GarbageAssets, GarbageBundle, GarbageItem};
+
+pub struct FragmentsPlugin;
+
+impl Plugin for FragmentsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, fragment_destroyed_items);
+    }
+}
+
+/// Spawns the catalog fragments of destroyed items side by side, along the
+/// longest axis of the parent, with its velocity
+fn fragment_destroyed_items(
+    mut commands: Commands,
+    assets: Option<Res<GarbageAssets>>,
+    items: Query<
+        (
+            Entity,
+            &GarbageItem,
+            &GlobalTransform,
+            Option<&LinearVelocity>,
+            Option<&AngularVelocity>,
+        ),
+        Added<Dead>,
+    >,
+) {
+    let Some(assets) = assets else {
+        return;
+    };
+    for (entity, item, gtr, linvel, angvel) in &items {
+        let fragments: Vec<_> = assets.catalog.fragments(*item).collect();
+        if fragments.is_empty() {
+            continue;
+        }
+        let transform = gtr.compute_transform();
+        let size = assets.catalog[*item].shape.size();
+        // Longest local axis of the parent
+        let axis = if size.x >= size.y.max(size.z) {
+            Vec3::X
+        } else if size.y >= size.z {
+            Vec3::Y
+        } else {
+            Vec3::Z
+        };
+        // Fragments keep the parent orientation, their extents along the axis
+        // are laid end to end and centered on the parent
+        let extents: Vec<_> = fragments
+            .iter()
+            .map(|fragment| assets.catalog[*fragment].shape.size().dot(axis))
+            .collect();
+        let mut start = -extents.iter().sum::<f32>() / 2.0;
+        for (fragment, extent) in fragments.into_iter().zip(extents) {
+            let offset = axis * (start + extent / 2.0);
+            start += extent;
+            let mut bundle = GarbageBundle::new(fragment, &assets);
+            bundle.pbr.transform = transform.with_translation(transform.transform_point(offset));
+            commands.spawn((
+                bundle,
+                linvel.copied().unwrap_or_default(),
+                angvel.copied().unwrap_or_default(),
+            ));
+        }
+        // The parent is only despawned next frame, its collider would overlap
+        // the fragments in the meantime
+        commands.entity(entity).remove::<Collider>();
+    }
+}
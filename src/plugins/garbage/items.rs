@@ -1,79 +1,245 @@
+use super::behaviors::ItemBehavior;
 use crate::{plugins::particles::DeathEffect, Health, ObjectLayer};
 use avian3d::prelude::*;
-use bevy::{color::palettes::css::*, prelude::*};
-use strum::{Display, EnumIter, IntoEnumIterator};
+use bevy::{
+    asset::{AssetLoader, AsyncReadExt},
+    ecs::component::{ComponentHooks, StorageType},
+    log,
+    prelude::*,
+};
+use serde::{Deserialize, Deserializer};
+use std::{f32::consts::PI, ops::Index};
+use thiserror::Error;
 
-#[derive(Debug, Copy, Clone, Component, EnumIter, Reflect, Display)]
-#[non_exhaustive]
-#[repr(u8)]
+/// Path of the garbage item catalog asset
+pub const CATALOG_PATH: &str = "items.catalog";
+/// Glyphs reserved by the `.build` format
+const RESERVED_GLYPHS: [char; 4] = ['.', ' ', '-', '~'];
+
+pub struct GarbageItemsPlugin;
+
+impl Plugin for GarbageItemsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<GarbageCatalog>()
+            .preregister_asset_loader::<CatalogLoader>(&["catalog"])
+            .init_asset_loader::<CatalogLoader>()
+            .register_type::<GarbageItem>()
+            .register_type::<GarbageAssets>()
+            .add_systems(Startup, setup_catalog)
+            .add_systems(Update, sync_garbage_assets);
+    }
+}
+
+/// Garbage item kind, identified by the glyph of its definition in the
+/// [`GarbageCatalog`]. Glyphs are unique and unlike positions survive the
+/// catalog being reordered on reload
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect)]
 #[reflect(Component)]
-pub enum GarbageItem {
-    /// 1x1
-    Cube,
-    /// 2x0.5
-    Plank,
-    /// 3x0.5
-    LargePlank,
-    /// 2x1
-    Column,
-    /// 3x1
-    LargeColumn,
-    /// 2x1
-    Block,
-    /// 2x1
-    Cone,
-    /// 3x1
-    LargeBlock,
-    /// 1x1
-    Ball,
+pub struct GarbageItem(char);
+
+impl Component for GarbageItem {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_add(|mut world, entity, _| {
+            let item = *world.get::<Self>(entity).unwrap();
+            let Some(behavior) = world
+                .get_resource::<GarbageAssets>()
+                .and_then(|assets| assets.catalog.get(item))
+                .and_then(|def| def.behavior)
+            else {
+                return;
+            };
+            behavior.insert(&mut world.commands().entity(entity));
+        });
+    }
 }
 
 impl GarbageItem {
     pub const MAX_SPEED: f32 = 90.0;
+    pub const LINEAR_DAMPING: f32 = 0.5;
 
-    pub fn color(self) -> Color {
-        match self {
-            Self::Cube => SANDY_BROWN.into(),
-            Self::Plank => PERU.into(),
-            Self::LargePlank => CHOCOLATE.into(),
-            Self::Column => SIENNA.into(),
-            Self::LargeColumn => SADDLE_BROWN.into(),
-            Self::Block => DARK_GOLDENROD.into(),
-            Self::Cone => DARK_ORANGE.into(),
-            Self::LargeBlock => CORAL.into(),
-            Self::Ball => TOMATO.into(),
+    /// Returns the item represented by `c` in `.build` files
+    pub fn from_char(c: char, catalog: &GarbageCatalog) -> Option<Self> {
+        let item = Self(c);
+        catalog.position(item).map(|_| item)
+    }
+}
+
+/// Garbage item definitions, loaded from [`CATALOG_PATH`]
+#[derive(Debug, Clone, Default, Asset, TypePath, Deserialize)]
+#[serde(transparent)]
+pub struct GarbageCatalog {
+    items: Vec<ItemDefinition>,
+}
+
+impl GarbageCatalog {
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Iterates over every item kind of the catalog
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = GarbageItem> + '_ {
+        self.items.iter().map(|def| GarbageItem(def.glyph))
+    }
+
+    /// Returns the index of the `item` definition, and of its runtime assets
+    /// in [`GarbageAssets`]
+    pub fn position(&self, item: GarbageItem) -> Option<usize> {
+        self.items.iter().position(|def| def.glyph == item.0)
+    }
+
+    pub fn get(&self, item: GarbageItem) -> Option<&ItemDefinition> {
+        self.position(item).map(|i| &self.items[i])
+    }
+
+    /// Returns the item kind named `name`
+    pub fn find(&self, name: &str) -> Option<GarbageItem> {
+        self.items
+            .iter()
+            .find(|def| def.name == name)
+            .map(|def| GarbageItem(def.glyph))
+    }
+
+    /// Returns the item kinds `item` breaks into when destroyed
+    pub fn fragments(&self, item: GarbageItem) -> impl Iterator<Item = GarbageItem> + '_ {
+        self[item]
+            .fragments
+            .iter()
+            .filter_map(|name| self.find(name))
+    }
+
+    fn validate(&self) -> Result<(), CatalogAssetError> {
+        if self.items.is_empty() {
+            return Err(CatalogAssetError::Empty);
         }
+        for (i, def) in self.items.iter().enumerate() {
+            if RESERVED_GLYPHS.contains(&def.glyph) {
+                return Err(CatalogAssetError::ReservedGlyph {
+                    glyph: def.glyph,
+                    item: def.name.clone(),
+                });
+            }
+            if let Some(other) = self.items[..i].iter().find(|o| o.glyph == def.glyph) {
+                return Err(CatalogAssetError::DuplicateGlyph {
+                    glyph: def.glyph,
+                    first: other.name.clone(),
+                    second: def.name.clone(),
+                });
+            }
+            if let Some(fragment) = def.fragments.iter().find(|f| self.find(f).is_none()) {
+                return Err(CatalogAssetError::UnknownFragment {
+                    item: def.name.clone(),
+                    fragment: fragment.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Index<GarbageItem> for GarbageCatalog {
+    type Output = ItemDefinition;
+
+    fn index(&self, item: GarbageItem) -> &Self::Output {
+        self.get(item)
+            .unwrap_or_else(|| panic!("Garbage item `{}` is not in the catalog", item.0))
+    }
+}
+
+/// Definition of a garbage item kind
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemDefinition {
+    pub name: String,
+    /// Glyph representing the item in `.build` files
+    pub glyph: char,
+    pub shape: ItemShape,
+    /// Hexadecimal color, like `"#F4A460"`
+    #[serde(deserialize_with = "hex_color")]
+    pub color: Color,
+    #[serde(default = "default_factor")]
+    pub density: f32,
+    pub health: u16,
+    /// Applied to the damage dealt by the item when thrown
+    #[serde(default = "default_factor")]
+    pub damage_multiplier: f32,
+    /// Names of the items spawned when this one is destroyed
+    #[serde(default)]
+    pub fragments: Vec<String>,
+    #[serde(default)]
+    pub behavior: Option<ItemBehavior>,
+}
+
+impl ItemDefinition {
+    /// Mass of the item, matching the one computed by the physics engine from
+    /// its collider and density
+    pub fn mass(&self) -> f32 {
+        self.density * self.shape.volume()
     }
+}
 
+const fn default_factor() -> f32 {
+    1.0
+}
+
+fn hex_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    Srgba::hex(&hex)
+        .map(Color::from)
+        .map_err(serde::de::Error::custom)
+}
+
+/// Primitive shape of a garbage item, used for both its mesh and collider
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum ItemShape {
+    Cuboid { x: f32, y: f32, z: f32 },
+    Cylinder { radius: f32, height: f32 },
+    Cone { radius: f32, height: f32 },
+    Sphere { radius: f32 },
+}
+
+impl ItemShape {
     pub fn mesh(self) -> Mesh {
         match self {
-            Self::Cube => Cuboid::from_size(Vec3::ONE).into(),
-            Self::Plank => Cuboid::new(2.0, 0.5, 1.0).into(),
-            Self::LargePlank => Cuboid::new(3.0, 0.5, 1.0).into(),
-            Self::Column => Cylinder::new(0.5, 2.0).into(),
-            Self::LargeColumn => Cylinder::new(0.5, 3.0).into(),
-            Self::Cone => Cone {
-                radius: 1.0,
-                height: 1.5,
+            Self::Cuboid { x, y, z } => Cuboid::new(x, y, z).into(),
+            Self::Cylinder { radius, height } => Cylinder::new(radius, height).into(),
+            Self::Cone { radius, height } => Cone { radius, height }.into(),
+            Self::Sphere { radius } => Sphere::new(radius).mesh().ico(24).unwrap(),
+        }
+    }
+
+    pub fn volume(self) -> f32 {
+        match self {
+            Self::Cuboid { x, y, z } => x * y * z,
+            Self::Cylinder { radius, height } => PI * radius * radius * height,
+            Self::Cone { radius, height } => PI * radius * radius * height / 3.0,
+            Self::Sphere { radius } => 4.0 / 3.0 * PI * radius.powi(3),
+        }
+    }
+
+    /// Axis aligned size of the shape
+    pub const fn size(self) -> Vec3 {
+        match self {
+            Self::Cuboid { x, y, z } => Vec3::new(x, y, z),
+            Self::Cylinder { radius, height } | Self::Cone { radius, height } => {
+                Vec3::new(radius * 2.0, height, radius * 2.0)
             }
-            .into(),
-            Self::Block => Cuboid::new(1.0, 2.0, 1.0).into(),
-            Self::LargeBlock => Cuboid::new(1.0, 3.0, 1.0).into(),
-            Self::Ball => Sphere::new(1.0).mesh().ico(24).unwrap(),
+            Self::Sphere { radius } => Vec3::splat(radius * 2.0),
         }
     }
 
     pub fn collider(self) -> Collider {
         match self {
-            Self::Cube => Collider::cuboid(1.0, 1.0, 1.0),
-            Self::Plank => Collider::cuboid(2.0, 0.5, 1.0),
-            Self::LargePlank => Collider::cuboid(3.0, 0.5, 1.0),
-            Self::Column => Collider::cylinder(0.5, 2.0),
-            Self::LargeColumn => Collider::cylinder(0.5, 3.0),
-            Self::Cone => Collider::cone(1.0, 1.5),
-            Self::Block => Collider::cuboid(1.0, 2.0, 1.0),
-            Self::LargeBlock => Collider::cuboid(1.0, 3.0, 1.0),
-            Self::Ball => Collider::sphere(1.0),
+            Self::Cuboid { x, y, z } => Collider::cuboid(x, y, z),
+            Self::Cylinder { radius, height } => Collider::cylinder(radius, height),
+            Self::Cone { radius, height } => Collider::cone(radius, height),
+            Self::Sphere { radius } => Collider::sphere(radius),
         }
     }
 }
@@ -90,69 +256,193 @@ pub struct GarbageBundle {
     pub ang_damping: AngularDamping,
     pub gravity_scale: GravityScale,
     pub name: Name,
+    pub density: ColliderDensity,
     pub health: Health,
     pub death: DeathEffect,
 }
 
 impl GarbageBundle {
+    /// # Panics
+    ///
+    /// If `collectible` is not in the catalog of `assets`
     pub fn new(collectible: GarbageItem, assets: &GarbageAssets) -> Self {
+        let index = assets
+            .catalog
+            .position(collectible)
+            .expect("Garbage item is not in the catalog");
+        let def = &assets.catalog.items[index];
         Self {
             collectible,
             pbr: PbrBundle {
-                mesh: assets.meshes[collectible as usize].clone_weak(),
-                material: assets.materials[collectible as usize].clone_weak(),
+                mesh: assets.meshes[index].clone_weak(),
+                material: assets.materials[index].clone_weak(),
                 ..default()
             },
             rigidbody: RigidBody::Dynamic,
-            collider: assets.colliders[collectible as usize].clone(),
+            collider: assets.colliders[index].clone(),
             margin: CollisionMargin(0.02),
-            lin_damping: LinearDamping(0.5),
+            lin_damping: LinearDamping(GarbageItem::LINEAR_DAMPING),
             ang_damping: AngularDamping(1.5),
             layer: CollisionLayers::new(ObjectLayer::Collectible, LayerMask::ALL),
             gravity_scale: GravityScale(1.0),
-            name: Name::new(format!("{collectible}")),
-            health: Health::new(15),
+            name: Name::new(def.name.clone()),
+            density: ColliderDensity(def.density),
+            health: Health::new(def.health),
             death: DeathEffect {
-                color: collectible.color(),
+                color: def.color,
                 radius: 0.5,
             },
         }
     }
 }
 
+/// Runtime garbage item registry, built from the [`GarbageCatalog`] asset
 #[derive(Resource, Reflect)]
 #[reflect(Resource)]
 pub struct GarbageAssets {
+    #[reflect(ignore)]
+    pub catalog: GarbageCatalog,
     pub meshes: Vec<Handle<Mesh>>,
     #[reflect(ignore)]
     pub colliders: Vec<Collider>,
     pub materials: Vec<Handle<StandardMaterial>>,
 }
 
-impl FromWorld for GarbageAssets {
-    fn from_world(world: &mut World) -> Self {
-        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+impl GarbageAssets {
+    fn new(
+        catalog: GarbageCatalog,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Self {
         let base_material = StandardMaterial {
             fog_enabled: true,
             metallic: 0.0,
             perceptual_roughness: 0.8,
             ..default()
         };
-        let materials = GarbageItem::iter()
-            .map(|c| {
+        let materials = catalog
+            .items
+            .iter()
+            .map(|def| {
                 materials.add(StandardMaterial {
-                    base_color: c.color(),
+                    base_color: def.color,
                     ..base_material.clone()
                 })
             })
             .collect();
-        let mut meshes = world.resource_mut::<Assets<Mesh>>();
-        let meshes = GarbageItem::iter().map(|c| meshes.add(c.mesh())).collect();
-        let colliders = GarbageItem::iter().map(GarbageItem::collider).collect();
+        let meshes = catalog
+            .items
+            .iter()
+            .map(|def| meshes.add(def.shape.mesh()))
+            .collect();
+        let colliders = catalog
+            .items
+            .iter()
+            .map(|def| def.shape.collider())
+            .collect();
         Self {
+            catalog,
             meshes,
             materials,
             colliders,
         }
     }
 }
+
+#[derive(Resource)]
+struct CatalogHandle(Handle<GarbageCatalog>);
+
+fn setup_catalog(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CatalogHandle(asset_server.load(CATALOG_PATH)));
+}
+
+/// (Re)builds [`GarbageAssets`] when the catalog is loaded or modified, and
+/// refreshes the already spawned items, matched to their definition by glyph
+fn sync_garbage_assets(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<GarbageCatalog>>,
+    handle: Res<CatalogHandle>,
+    catalogs: Res<Assets<GarbageCatalog>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut items: Query<(
+        Entity,
+        &GarbageItem,
+        &mut Handle<Mesh>,
+        &mut Handle<StandardMaterial>,
+        &mut Collider,
+        &mut ColliderDensity,
+        &mut DeathEffect,
+    )>,
+) {
+    let reloaded = events
+        .read()
+        .any(|event| event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0));
+    if !reloaded {
+        return;
+    }
+    let Some(catalog) = catalogs.get(&handle.0) else {
+        return;
+    };
+    let assets = GarbageAssets::new(catalog.clone(), &mut meshes, &mut materials);
+    for (entity, item, mut mesh, mut material, mut collider, mut density, mut death) in &mut items {
+        let Some(index) = assets.catalog.position(*item) else {
+            log::warn!("{entity:?} item kind was removed from the catalog, despawning it");
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        let def = &assets.catalog.items[index];
+        *mesh = assets.meshes[index].clone_weak();
+        *material = assets.materials[index].clone_weak();
+        *collider = assets.colliders[index].clone();
+        density.0 = def.density;
+        death.color = def.color;
+    }
+    log::info!("Loaded {} garbage item kinds", assets.catalog.len());
+    commands.insert_resource(assets);
+}
+
+#[derive(Default)]
+pub struct CatalogLoader;
+
+/// Possible errors that can be produced by [`CatalogLoader`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum CatalogAssetError {
+    /// An [IO](std::io) Error
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid catalog: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Glyph `{glyph}` of `{item}` is reserved by the build format")]
+    ReservedGlyph { glyph: char, item: String },
+    #[error("Glyph `{glyph}` is used by both `{first}` and `{second}`")]
+    DuplicateGlyph {
+        glyph: char,
+        first: String,
+        second: String,
+    },
+    #[error("Fragment `{fragment}` of `{item}` is not in the catalog")]
+    UnknownFragment { item: String, fragment: String },
+    #[error("Catalog has no items")]
+    Empty,
+}
+
+impl AssetLoader for CatalogLoader {
+    type Asset = GarbageCatalog;
+    type Settings = ();
+    type Error = CatalogAssetError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut bevy::asset::io::Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut bevy::asset::LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let catalog: GarbageCatalog = ron::de::from_bytes(&bytes)?;
+        catalog.validate()?;
+        Ok(catalog)
+    }
+}
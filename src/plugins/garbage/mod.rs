@@ -1,28 +1,39 @@
-use bevy::{ecs::world::Command, prelude::*};
-use std::f32::consts::PI;
+use bevy::{log, prelude::*};
 
+mod behaviors;
 mod body;
 mod builds;
 mod collected;
 mod collector;
+mod damage_drop;
 mod distribution;
+mod fragments;
 mod items;
+mod placement;
+mod stealing;
 mod throw;
 
-pub use body::{GarbageBody, GarbageBodyPlugin};
+pub use body::{Chain, GarbageBody, GarbageBodyPlugin, LimbParent};
 
-pub use builds::{AvailableItemBuilds, SpawnBuild};
+pub use builds::{AvailableItemBuilds, SaveBuild, SpawnBuild};
 pub use collected::Collected;
-pub use collector::{Collector, CollectorBundle, CollectorConfig, CollectorParticlesBundle};
+pub use collector::{
+    Collector, CollectorBundle, CollectorConfig, CollectorParticlesBundle, ThrowPolicy,
+};
 pub use distribution::{DistributionShape, PointDistribution};
 pub use items::{GarbageAssets, GarbageBundle, GarbageItem};
-pub use throw::ThrownItem;
+pub use placement::{place_builds, BuildPlacement};
+pub use throw::{ballistic_position, ballistic_velocity, ThrownItem};
 
+use behaviors::BehaviorsPlugin;
 use builds::ItemBuildsPlugin;
 use collected::CollectedPlugin;
 use collector::CollectorPlugin;
-use rand::{seq::IteratorRandom, thread_rng, Rng};
-use strum::IntoEnumIterator;
+use damage_drop::DamageDropPlugin;
+use fragments::FragmentsPlugin;
+use items::GarbageItemsPlugin;
+use rand::{seq::IteratorRandom, thread_rng};
+use stealing::StealingPlugin;
 use throw::ThrowPlugin;
 
 use super::map::MAP_SIZE;
@@ -34,13 +45,17 @@ impl Plugin for GarbagePlugin {
         app.add_plugins((
             CollectedPlugin,
             CollectorPlugin,
+            DamageDropPlugin,
+            GarbageItemsPlugin,
+            FragmentsPlugin,
+            BehaviorsPlugin,
             ItemBuildsPlugin,
             ThrowPlugin,
+            StealingPlugin,
             GarbageBodyPlugin,
         ))
-        .init_resource::<GarbageAssets>()
-        .register_type::<GarbageAssets>()
-        .register_type::<GarbageItem>()
+        .init_resource::<BuildPlacement>()
+        .register_type::<BuildPlacement>()
         .register_type::<PointDistribution>();
     }
 }
@@ -55,15 +70,18 @@ pub fn spawn_some_garbage(
     move |world| {
         let square = Rectangle::new(size.x, size.y);
         let mut rng = thread_rng();
-        let assets = world.resource::<GarbageAssets>();
+        let Some(assets) = world.get_resource::<GarbageAssets>() else {
+            log::warn!("Garbage catalog is not loaded, can't spawn garbage");
+            return;
+        };
         let bundles: Vec<_> = (0..amount)
-            .map(|_| {
-                let item = GarbageItem::iter().choose(&mut rng).unwrap();
+            .filter_map(|_| {
+                let item = assets.catalog.iter().choose(&mut rng)?;
                 let pos = offset + square.sample_interior(&mut rng);
                 let position = Vec3::new(pos.x, 1.0, pos.y);
                 let mut bundle = GarbageBundle::new(item, assets);
                 bundle.pbr.transform.translation = position;
-                bundle
+                Some(bundle)
             })
             .collect();
         world.spawn_batch(bundles);
@@ -75,27 +93,7 @@ pub fn spawn_builds(
     size: Option<Vec2>,
     offset: Option<Vec2>,
 ) -> impl FnOnce(&mut World) {
-    let size = size.unwrap_or(MAP_SIZE - Vec2::splat(20.0));
-    let offset = offset.unwrap_or(Vec2::ZERO);
     move |world| {
-        let square = Rectangle::new(size.x, size.y);
-        let mut rng = thread_rng();
-        let builds = world.resource::<AvailableItemBuilds>();
-        let commands: Vec<_> = (0..amount)
-            .map(|_| {
-                let handle = builds.values().choose(&mut rng).unwrap().clone_weak();
-                let pos = offset + square.sample_interior(&mut rng);
-                let position = Vec3::new(pos.x, 1.0, pos.y);
-                let angle = rng.gen_range(0.0..PI);
-                SpawnBuild {
-                    handle,
-                    position,
-                    angle,
-                }
-            })
-            .collect();
-        for command in commands {
-            command.apply(world);
-        }
+        place_builds(world, amount, size, offset, &[]);
     }
 }
@@ -0,0 +1,163 @@
+use super::{builds::ItemBuild, AvailableItemBuilds, SpawnBuild};
+use crate::{plugins::map::MAP_SIZE, Enemy, Player, StartGame};
+use bevy::{ecs::world::Command, log, prelude::*, utils::HashMap};
+use rand::{distributions::WeightedIndex, prelude::Distribution, thread_rng, Rng};
+use std::f32::consts::PI;
+
+/// Builds with this amount of items or less get a weight of `1.0`, bigger
+/// builds are proportionally less likely to spawn
+const REFERENCE_ITEM_COUNT: f32 = 10.0;
+
+/// Placement rules used by [`place_builds`]
+#[derive(Debug, Clone, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct BuildPlacement {
+    /// Weight overrides, indexed by build path. Builds without an override are
+    /// weighted by their item count
+    pub weights: HashMap<String, f32>,
+    /// Minimum gap between two build footprints
+    pub spacing: f32,
+    /// Radius around players, enemies and game starters kept clear of builds
+    pub exclusion_radius: f32,
+    /// Maximum amount of positions tried for every build
+    pub max_attempts: usize,
+}
+
+impl Default for BuildPlacement {
+    fn default() -> Self {
+        Self {
+            weights: HashMap::new(),
+            spacing: 2.0,
+            exclusion_radius: 8.0,
+            max_attempts: 10,
+        }
+    }
+}
+
+impl BuildPlacement {
+    pub fn weight(&self, path: &str, build: &ItemBuild) -> f32 {
+        self.weights
+            .get(path)
+            .copied()
+            .unwrap_or_else(|| (REFERENCE_ITEM_COUNT / build.len() as f32).min(1.0))
+    }
+}
+
+/// Places up to `amount` builds in a `size` area centered on `offset`,
+/// following the [`BuildPlacement`] rules.
+///
+/// `exclusions` are additional positions to keep clear, for entities which are
+/// not spawned yet. Returns the amount of builds actually placed, a shortfall
+/// is logged as a warning.
+pub fn place_builds(
+    world: &mut World,
+    amount: usize,
+    size: Option<Vec2>,
+    offset: Option<Vec2>,
+    exclusions: &[Vec2],
+) -> usize {
+    let size = size.unwrap_or(MAP_SIZE - Vec2::splat(20.0));
+    let offset = offset.unwrap_or(Vec2::ZERO);
+    // Apply pending despawns, from `clear_all` for instance
+    world.flush();
+    let rules = world
+        .get_resource::<BuildPlacement>()
+        .cloned()
+        .unwrap_or_default();
+    // Global transforms may not be propagated yet, all these entities are on
+    // the root or under an identity root
+    let mut zones =
+        world.query_filtered::<&Transform, Or<(With<Player>, With<Enemy>, With<StartGame>)>>();
+    let zones: Vec<Vec2> = zones
+        .iter(world)
+        .map(|tr| tr.translation.xz())
+        .chain(exclusions.iter().copied())
+        .collect();
+
+    let assets = world.resource::<Assets<ItemBuild>>();
+    let candidates: Vec<_> = world
+        .resource::<AvailableItemBuilds>()
+        .iter()
+        .filter_map(|(path, handle)| {
+            let build = assets.get(handle)?;
+            Some((handle, rules.weight(path, build), build.footprint()))
+        })
+        .collect();
+    let Ok(distribution) = WeightedIndex::new(candidates.iter().map(|(_, weight, _)| *weight))
+    else {
+        log::warn!("No build available for placement");
+        return 0;
+    };
+
+    let square = Rectangle::new(size.x, size.y);
+    let mut rng = thread_rng();
+    let mut placed: Vec<(Vec2, f32)> = Vec::with_capacity(amount);
+    let mut commands = Vec::with_capacity(amount);
+    for _ in 0..amount {
+        let (handle, _, (center, radius)) = candidates[distribution.sample(&mut rng)];
+        for _ in 0..rules.max_attempts {
+            let pos = offset + square.sample_interior(&mut rng);
+            let angle = rng.gen_range(0.0..PI);
+            let rotated = Quat::from_rotation_y(angle) * Vec3::new(center.x, 0.0, center.y);
+            let center = pos + rotated.xz();
+            let blocked = placed
+                .iter()
+                .any(|(c, r)| c.distance(center) < r + radius + rules.spacing)
+                || zones
+                    .iter()
+                    .any(|z| z.distance(center) < radius + rules.exclusion_radius);
+            if blocked {
+                continue;
+            }
+            placed.push((center, radius));
+            commands.push(SpawnBuild {
+                handle: handle.clone_weak(),
+                position: Vec3::new(pos.x, 1.0, pos.y),
+                angle,
+            });
+            break;
+        }
+    }
+    let count = commands.len();
+    if count < amount {
+        log::warn!("Only {count} out of {amount} builds could be placed");
+    }
+    for command in commands {
+        command.apply(world);
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::garbage::items::GarbageCatalog;
+
+    /// World with a single one item build available
+    fn world() -> World {
+        let catalog: GarbageCatalog =
+            ron::de::from_str(include_str!("../../../assets/items.catalog")).unwrap();
+        let mut world = World::new();
+        world.init_resource::<Assets<ItemBuild>>();
+        let build = ItemBuild::parse_strict(b"c", &catalog).unwrap();
+        let handle = world.resource_mut::<Assets<ItemBuild>>().add(build);
+        world.insert_resource(AvailableItemBuilds(
+            [("builds/cube.build".to_owned(), handle)].into(),
+        ));
+        world.insert_resource(BuildPlacement {
+            max_attempts: 100,
+            ..default()
+        });
+        world
+    }
+
+    #[test]
+    fn exclusions_keep_builds_away() {
+        let mut world = world();
+        let size = Some(Vec2::splat(4.0));
+        // The whole area is within the exclusion radius
+        assert_eq!(place_builds(&mut world, 5, size, None, &[Vec2::X]), 0);
+        let far = Vec2::X * (BuildPlacement::default().exclusion_radius + 5.0);
+        assert_eq!(place_builds(&mut world, 1, size, None, &[far]), 1);
+    }
+}
@@ -0,0 +1,75 @@
+use std::{cmp::Ordering, time::Duration};
+
+use avian3d::prelude::*;
+use bevy::{prelude::*, time::common_conditions::on_timer};
+
+use crate::{FriendlyFire, GameState, Teams};
+
+use super::{Collected, Collector, CollectorConfig};
+
+pub struct StealingPlugin;
+
+/// Delay between two items stolen from a collector
+const STEAL_INTERVAL: Duration = Duration::from_millis(250);
+
+impl Plugin for StealingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            steal_items
+                .run_if(in_state(GameState::Running))
+                .run_if(on_timer(STEAL_INTERVAL)),
+        );
+    }
+}
+
+/// Moves items from the weaker of two overlapping enabled collectors to the
+/// stronger one, if their owners are not on the same [`Team`](crate::Team). The
+/// `Collected` hooks keep both collectors and the item collision layers in
+/// sync.
+fn steal_items(
+    mut commands: Commands,
+    collectors: Query<(Entity, &GlobalTransform, &Collector, &CollectorConfig)>,
+    teams: Teams,
+    items: Query<&ColliderMassProperties>,
+) {
+    let strength = |collector: &Collector, config: &CollectorConfig| {
+        config.grip.unwrap_or(collector.len() as f32)
+    };
+    for [(entity_a, gtr_a, collector_a, config_a), (entity_b, gtr_b, collector_b, config_b)] in
+        collectors.iter_combinations()
+    {
+        if !config_a.enabled || !config_b.enabled {
+            continue;
+        }
+        if FriendlyFire::allies(
+            teams.collector_team(entity_a),
+            teams.collector_team(entity_b),
+        ) {
+            continue;
+        }
+        let distance = gtr_a.translation().distance(gtr_b.translation());
+        if distance > collector_a.sensor_radius() + collector_b.sensor_radius() {
+            continue;
+        }
+        let (strong_entity, strong, weak) =
+            match strength(collector_a, config_a).total_cmp(&strength(collector_b, config_b)) {
+                Ordering::Greater => (entity_a, collector_a, collector_b),
+                Ordering::Less => (entity_b, collector_b, collector_a),
+                Ordering::Equal => continue,
+            };
+        let Some(item) = weak.collected().last().copied() else {
+            continue;
+        };
+        let mass = items.get(item).map_or(0.0, |p| p.mass.0);
+        if !strong.can_collect(mass) {
+            continue;
+        }
+        commands
+            .entity(item)
+            .remove::<Collected>()
+            .insert(Collected {
+                collector_entity: strong_entity,
+            });
+    }
+}
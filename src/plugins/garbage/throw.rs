@@ -6,9 +6,9 @@ use bevy::{
 };
 use bevy_mod_outline::{OutlineBundle, OutlineVolume};
 
-use crate::Damage;
+use crate::{Damage, DamageSource, DamageType, FriendlyFire, ImpactDamage, Teams};
 
-use super::{collector::CollectorConfig, Collected};
+use super::{collector::CollectorConfig, GarbageAssets, GarbageItem};
 
 pub struct ThrowPlugin;
 
@@ -42,6 +42,18 @@ impl Component for ThrownItem {
                     return;
                 };
                 let color = config.color;
+                let multiplier = world
+                    .get::<GarbageItem>(entity)
+                    .zip(world.get_resource::<GarbageAssets>())
+                    .map_or(1.0, |(item, assets)| {
+                        assets.catalog[*item].damage_multiplier
+                    });
+                let damage = (f32::from(THROW_DAMAGE) * multiplier).round() as u16;
+                // Damage is attributed to the collector owner
+                let collector = thrown.collector_entity;
+                let instigator = world
+                    .get::<Parent>(collector)
+                    .map_or(collector, Parent::get);
                 let mut commands = world.commands();
                 commands.entity(entity).insert((
                     OutlineBundle {
@@ -52,15 +64,21 @@ impl Component for ThrownItem {
                         },
                         ..default()
                     },
-                    Damage(THROW_DAMAGE),
+                    Damage::new(damage, DamageType::Blunt),
+                    ImpactDamage::default(),
+                    DamageSource {
+                        collector,
+                        instigator,
+                    },
                 ));
             })
             .on_remove(|mut world, entity, _| {
                 let mut commands = world.commands();
-                commands
-                    .entity(entity)
-                    .remove::<OutlineBundle>()
-                    .remove::<Damage>();
+                commands.entity(entity).remove::<OutlineBundle>().remove::<(
+                    Damage,
+                    ImpactDamage,
+                    DamageSource,
+                )>();
             });
     }
 }
@@ -72,6 +90,55 @@ impl ThrownItem {
             timer: 0.0,
         }
     }
+
+    /// Restarts the minimum throw duration, keeping the item thrown for longer
+    pub const fn reset_timer(&mut self) {
+        self.timer = 0.0;
+    }
+}
+
+/// Distance factor `(1 - e^(-ct)) / c` of a body with linear `damping` c
+/// after `time`
+fn damping_factor(time: f32, damping: f32) -> f32 {
+    if damping <= f32::EPSILON {
+        time
+    } else {
+        -(-damping * time).exp_m1() / damping
+    }
+}
+
+/// Computes the launch velocity for a body under `gravity` and linear
+/// `damping` to reach `to` from `from` in `flight_time` seconds
+pub fn ballistic_velocity(
+    from: Vec3,
+    to: Vec3,
+    flight_time: f32,
+    gravity: Vec3,
+    damping: f32,
+) -> Vec3 {
+    let flight_time = flight_time.max(f32::EPSILON);
+    let factor = damping_factor(flight_time, damping);
+    if damping <= f32::EPSILON {
+        return (to - from) / flight_time - gravity * flight_time / 2.0;
+    }
+    let drift = gravity / damping;
+    (to - from - drift * flight_time) / factor + drift
+}
+
+/// Computes the position after `time` seconds of a body launched from `from`
+/// at `velocity` under `gravity` and linear `damping`
+pub fn ballistic_position(
+    from: Vec3,
+    velocity: Vec3,
+    time: f32,
+    gravity: Vec3,
+    damping: f32,
+) -> Vec3 {
+    if damping <= f32::EPSILON {
+        return from + velocity * time + gravity * time * time / 2.0;
+    }
+    let drift = gravity / damping;
+    from + drift * time + (velocity - drift) * damping_factor(time, damping)
 }
 
 fn update_thrown_items(
@@ -92,33 +159,66 @@ fn update_thrown_items(
 
 fn filter_thrown_collisions(
     mut collisions: ResMut<Collisions>,
+    friendly_fire: Res<FriendlyFire>,
     thrown: Query<&ThrownItem>,
-    collected: Query<&Collected>,
+    teams: Teams,
 ) {
+    // Returns `false` if the contact between the `item` and `other` should be
+    // ignored
+    let keep_contact = |item: Entity, other: Entity| {
+        let Ok(thrown_item) = thrown.get(item) else {
+            return true;
+        };
+        // Items are never hit by throws from their own collector
+        let collector = thrown_item.collector_entity;
+        if teams
+            .collected
+            .get(other)
+            .is_ok_and(|c| c.collector_entity == collector)
+        {
+            return false;
+        }
+        // Throws pass through teammates and their items without friendly fire
+        *friendly_fire != FriendlyFire::Off
+            || !FriendlyFire::allies(teams.collector_team(collector), teams.team(other))
+    };
     collisions.retain(|contact| {
-        let entities = [contact.entity1, contact.entity2];
-        let mut thrown_item_collector: Option<Entity> = None;
-        let mut collected_item_collector: Option<Entity> = None;
+        keep_contact(contact.entity1, contact.entity2)
+            && keep_contact(contact.entity2, contact.entity1)
+    });
+}
 
-        for &entity in &entities {
-            if let Ok(thrown_item) = thrown.get(entity) {
-                thrown_item_collector = Some(thrown_item.collector_entity);
-            }
-            if let Ok(collected_item) = collected.get(entity) {
-                collected_item_collector = Some(collected_item.collector_entity);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ballistic_round_trip() {
+        let from = Vec3::new(1.0, 2.0, -3.0);
+        let to = Vec3::new(-8.0, 0.5, 6.0);
+        let gravity = Vec3::NEG_Y * 9.81;
+        for damping in [0.0, 0.1, 0.8, 2.0] {
+            for flight_time in [0.25, 1.0, 2.5] {
+                let velocity = ballistic_velocity(from, to, flight_time, gravity, damping);
+                let landing = ballistic_position(from, velocity, flight_time, gravity, damping);
+                assert!(
+                    landing.distance(to) < 1e-3,
+                    "damping {damping}, time {flight_time}: {landing}"
+                );
+                assert_eq!(
+                    ballistic_position(from, velocity, 0.0, gravity, damping),
+                    from
+                );
             }
         }
+    }
 
-        // If both a thrown item and a collected item are found, compare their
-        // collector_entity fields
-        if let (Some(thrown_collector), Some(collected_collector)) =
-            (thrown_item_collector, collected_item_collector)
-        {
-            thrown_collector != collected_collector
-        } else {
-            // If either is None, we don't have a match and thus don't filter out the
-            // collision
-            true
-        }
-    });
+    #[test]
+    fn damped_trajectory_stays_below_undamped_one() {
+        let gravity = Vec3::NEG_Y * 9.81;
+        let velocity = Vec3::new(10.0, 10.0, 0.0);
+        let free = ballistic_position(Vec3::ZERO, velocity, 1.0, gravity, 0.0);
+        let damped = ballistic_position(Vec3::ZERO, velocity, 1.0, gravity, 0.5);
+        assert!(damped.x < free.x && damped.y < free.y, "{free} {damped}");
+    }
 }
@@ -12,7 +12,7 @@ use bevy::{
 };
 use bevy_hanabi::{EffectProperties, ParticleEffect, ParticleEffectBundle};
 
-use super::ParticleConfig;
+use super::{garbage::GarbageAssets, ParticleConfig};
 
 pub const MAP_SIZE: Vec2 = Vec2::new(200.0, 200.);
 
@@ -23,8 +23,15 @@ impl Plugin for MapPlugin {
         app.register_type::<Map>()
             .init_resource::<MapAssets>()
             .register_type::<MapAssets>()
-            .add_systems(Startup, (create_default_ground, spawn_game_starters))
-            .add_systems(Update, handle_game_starters);
+            .add_systems(Startup, create_default_ground)
+            // Game starters come with garbage, which requires the item catalog
+            .add_systems(
+                Update,
+                (
+                    spawn_game_starters.run_if(resource_added::<GarbageAssets>),
+                    handle_game_starters,
+                ),
+            );
     }
 }
 
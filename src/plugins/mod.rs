@@ -16,7 +16,10 @@ pub use common::*;
 #[cfg(feature = "debug")]
 pub use debug::DebugPlugin;
 pub use enemies::{spawn_enemies, EnemiesPlugin, Enemy};
-pub use garbage::{spawn_builds, spawn_some_garbage, GarbageItem, GarbagePlugin};
+pub use garbage::{
+    place_builds, spawn_some_garbage, Collector, DistributionShape, GarbageItem, GarbagePlugin,
+    SaveBuild,
+};
 pub use light::LightPlugin;
 pub use map::{spawn_game_starters, MapPlugin};
 pub use particles::{ParticleConfig, ParticlesPlugin};
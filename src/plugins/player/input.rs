@@ -92,6 +92,9 @@ pub enum PlayerInput {
     Move,
     Aim,
     Pause,
+    CycleAmmo,
+    /// Toggles lobbed throws for [`PlayerSkill::Shoot`]
+    ToggleLob,
     Skill(PlayerSkill),
 }
 
@@ -104,6 +107,8 @@ impl Display for PlayerInput {
                 Self::Move => "Move".into(),
                 Self::Aim => "Aim".into(),
                 Self::Pause => "Pause".into(),
+                Self::CycleAmmo => "Ammo".into(),
+                Self::ToggleLob => "Lob".into(),
                 Self::Skill(skill) => skill.to_string(),
             }
         )
@@ -123,8 +128,11 @@ impl PlayerInput {
                     .insert(Move, DualAxis::left_stick())
                     .insert(Move, VirtualDPad::dpad())
                     .insert(Aim, DualAxis::right_stick())
+                    .insert(CycleAmmo, GamepadButtonType::RightTrigger)
+                    .insert(ToggleLob, GamepadButtonType::RightThumb)
                     .insert(Skill(Collect), GamepadButtonType::South)
                     .insert(Skill(Shoot), GamepadButtonType::RightTrigger2)
+                    .insert(Skill(Salvo), GamepadButtonType::North)
                     .insert(Skill(Defend), GamepadButtonType::LeftTrigger2)
                     .insert(Skill(Dash), GamepadButtonType::East);
             }
@@ -133,8 +141,11 @@ impl PlayerInput {
                     .insert(Move, VirtualDPad::arrow_keys())
                     .insert(Move, VirtualDPad::wasd())
                     .insert(Aim, DualAxis::mouse_motion())
+                    .insert(CycleAmmo, KeyCode::KeyQ)
+                    .insert(ToggleLob, KeyCode::KeyF)
                     .insert_one_to_many(Skill(Collect), [KeyCode::ShiftLeft, KeyCode::ShiftRight])
                     .insert(Skill(Shoot), MouseButton::Left)
+                    .insert(Skill(Salvo), KeyCode::KeyR)
                     .insert(Skill(Defend), MouseButton::Right)
                     .insert(Skill(Dash), KeyCode::Space);
             }
@@ -1,7 +1,7 @@
 use super::{
     common::Health,
     garbage::{CollectorBundle, CollectorParticlesBundle},
-    Dead, Invincible,
+    Dead, Invincible, Team,
 };
 use crate::{ObjectLayer, ParticleConfig};
 use bevy::prelude::*;
@@ -58,6 +58,7 @@ pub struct Player {
 #[derive(Bundle)]
 pub struct PlayerBundle {
     pub player: Player,
+    pub team: Team,
     pub name: Name,
     pub health: Health,
     pub input: PlayerInputBundle,
@@ -73,6 +74,7 @@ impl PlayerBundle {
         }
         Self {
             name: Name::new(format!("Player {}: {}", player.id, player.controller)),
+            team: Team::PLAYERS,
             health: Health::new(BASE_PLAYER_HEALTH),
             input: PlayerInputBundle::new(player.controller, server),
             movement: PlayerMovementBundle::new(100.0, 0.9),
@@ -112,13 +114,12 @@ pub fn spawn_players(
         let player_entity = commands.spawn(bundle).set_parent(root_entity).id();
 
         let collector_entity = commands
-            .spawn(CollectorBundle::growing(
-                4.0,
-                1.0,
-                color,
-                50,
-                ObjectLayer::Player,
-            ))
+            .spawn(
+                CollectorBundle::growing(4.0, 1.0, color, 50, ObjectLayer::Player)
+                    .with_attraction(6.0, 40.0)
+                    .with_damage_drop(0.25, 15.0, 1.5)
+                    .with_max_mass(40.0),
+            )
             .set_parent(player_entity)
             .id();
         commands
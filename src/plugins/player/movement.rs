@@ -1,9 +1,10 @@
-use crate::{Dead, GameState, ObjectLayer};
+use crate::{Dead, GameState, HitStun, ObjectLayer};
 use avian3d::prelude::*;
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::*;
 
 use super::{assets::PlayerAssets, input::PlayerInput, Player, PLAYER_HEIGHT, PLAYER_RADIUS};
+use crate::plugins::garbage::Collector;
 
 pub struct PlayerMovementPlugin;
 
@@ -11,9 +12,15 @@ impl Plugin for PlayerMovementPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<MovementSpeed>()
             .register_type::<MovementDampingFactor>()
+            .register_type::<CarriedMass>()
             .add_systems(
                 Update,
-                (apply_gravity, apply_movement, apply_movement_damping)
+                (
+                    update_carried_mass,
+                    apply_gravity,
+                    apply_movement,
+                    apply_movement_damping,
+                )
                     .chain()
                     .run_if(in_state(GameState::Running)),
             );
@@ -35,9 +42,25 @@ pub struct MovementSpeed(pub f32);
 #[reflect(Component)]
 pub struct MovementDampingFactor(pub f32);
 
+/// Total mass of the items collected by the player collectors
+#[derive(Debug, Clone, Copy, Component, Reflect, Deref, DerefMut, PartialEq)]
+#[reflect(Component)]
+pub struct CarriedMass(pub f32);
+
+impl CarriedMass {
+    /// Speed reduction per carried mass unit
+    const PENALTY: f32 = 0.02;
+
+    /// Returns the movement speed multiplier for the carried mass
+    pub fn speed_factor(self) -> f32 {
+        1.0 / self.0.mul_add(Self::PENALTY, 1.0)
+    }
+}
+
 #[derive(Bundle)]
 pub struct PlayerMovementBundle {
     pub speed: MovementSpeed,
+    pub carried_mass: CarriedMass,
     pub damping: MovementDampingFactor,
     pub rigidbody: RigidBody,
     pub collider: Collider,
@@ -52,6 +75,7 @@ impl PlayerMovementBundle {
     pub fn new(speed: f32, damping_factor: f32) -> Self {
         Self {
             speed: MovementSpeed(speed),
+            carried_mass: CarriedMass(0.0),
             damping: MovementDampingFactor(damping_factor),
             rigidbody: RigidBody::Dynamic,
             collider: Collider::capsule(PLAYER_RADIUS, PLAYER_HEIGHT),
@@ -71,20 +95,32 @@ pub fn apply_movement(
             &mut LinearVelocity,
             &ActionState<PlayerInput>,
             &MovementSpeed,
+            &CarriedMass,
         ),
-        Without<Dead>,
+        (Without<Dead>, Without<HitStun>),
     >,
     time: Res<Time>,
 ) {
     let dt = time.delta_seconds();
-    for (mut velocity, action_state, speed) in &mut controllers {
+    for (mut velocity, action_state, speed, carried) in &mut controllers {
         if let Some(dir) = PlayerInput::get_movement(action_state) {
-            velocity.x += dir.x * dt * speed.0;
-            velocity.z -= dir.y * dt * speed.0;
+            let speed = speed.0 * carried.speed_factor();
+            velocity.x += dir.x * dt * speed;
+            velocity.z -= dir.y * dt * speed;
         }
     }
 }
 
+fn update_carried_mass(
+    mut players: Query<(&Children, &mut CarriedMass)>,
+    collectors: Query<&Collector>,
+) {
+    for (children, mut carried) in &mut players {
+        let mass = collectors.iter_many(children).map(Collector::mass).sum();
+        carried.set_if_neq(CarriedMass(mass));
+    }
+}
+
 /// Applies [`Gravity`] to player controllers.
 fn apply_gravity(
     time: Res<Time>,
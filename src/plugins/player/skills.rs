@@ -1,17 +1,21 @@
-use avian3d::prelude::{ExternalImpulse, LinearVelocity};
+use avian3d::prelude::{ExternalImpulse, Gravity, LinearVelocity};
 use bevy::{log, prelude::*, utils::HashMap};
 use leafwing_input_manager::action_state::ActionState;
+use std::f32::consts::FRAC_PI_3;
 use strum::{Display, EnumIter, IntoEnumIterator};
 
 use crate::{
     plugins::{
         camera::CameraParams,
-        garbage::{Collector, CollectorConfig, DistributionShape},
+        garbage::{
+            ballistic_position, ballistic_velocity, Collector, CollectorConfig, DistributionShape,
+            GarbageItem,
+        },
     },
-    Dead, GameState,
+    Dead, GameState, HitStun,
 };
 
-use super::{input::PlayerInput, GameController, Player};
+use super::{input::PlayerInput, movement::CarriedMass, GameController, Player};
 
 pub struct PlayerSkillsPlugin;
 
@@ -24,7 +28,18 @@ impl Plugin for PlayerSkillsPlugin {
                 Update,
                 (
                     (update_aim, apply_aim).chain(),
-                    (update_skills, (collector_skills, throw_skill, dash_skill)).chain(),
+                    (
+                        update_skills,
+                        (
+                            collector_skills,
+                            throw_skill,
+                            lob_throw_skill,
+                            salvo_skill,
+                            dash_skill,
+                        ),
+                    )
+                        .chain(),
+                    (cycle_ammo, toggle_lob),
                 )
                     .run_if(in_state(GameState::Running)),
             );
@@ -41,7 +56,10 @@ impl Plugin for PlayerSkillsPlugin {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Hash, EnumIter, Display)]
 pub enum PlayerSkill {
     Collect,
+    /// Hold to charge the throw force, thrown on input release
     Shoot,
+    /// Throws several items at once in a cone
+    Salvo,
     Dash,
     Defend,
 }
@@ -51,16 +69,36 @@ impl PlayerSkill {
         match self {
             Self::Collect => 0.0,
             Self::Shoot => 0.05,
+            Self::Salvo => 1.5,
             Self::Dash => 0.5,
             Self::Defend => 0.0,
         }
     }
 }
 
+const THROW_FORCE: f32 = 70.0;
+const CHARGED_THROW_MAX_FORCE: f32 = 140.0;
+/// Time in seconds to reach [`CHARGED_THROW_MAX_FORCE`]
+const CHARGED_THROW_DURATION: f32 = 1.5;
+const SALVO_FORCE: f32 = 60.0;
+const SALVO_COUNT: usize = 5;
+/// Total angle of the salvo cone
+const SALVO_SPREAD: f32 = FRAC_PI_3;
+/// Maximum lobbed throw range, reached at full gamepad stick tilt
+const LOB_MAX_RANGE: f32 = 25.0;
+/// Horizontal speed used to compute lobbed throws flight time
+const LOB_SPEED: f32 = 20.0;
+const LOB_MIN_FLIGHT_TIME: f32 = 0.5;
+const LOB_PREVIEW_STEPS: usize = 24;
+
 #[derive(Debug, Reflect, Component, Clone)]
 #[reflect(Component)]
 pub struct SkillState {
     pub cooldowns: HashMap<PlayerSkill, f32>,
+    /// Time spent charging [`PlayerSkill::Shoot`]
+    pub charge: f32,
+    /// Whether a lobbed [`PlayerSkill::Shoot`] is being aimed
+    pub lobbing: bool,
 }
 
 #[derive(Debug, Reflect, Component, Default, Clone)]
@@ -73,6 +111,8 @@ impl Default for SkillState {
     fn default() -> Self {
         Self {
             cooldowns: PlayerSkill::iter().map(|s| (s, 0.0)).collect(),
+            charge: 0.0,
+            lobbing: false,
         }
     }
 }
@@ -82,6 +122,10 @@ impl Default for SkillState {
 pub struct PlayerAim {
     pub dir: Dir2,
     pub max_rotation_speed: f32,
+    /// Ground point aimed at by lobbed throws
+    pub target: Vec3,
+    /// If enabled, [`PlayerSkill::Shoot`] lobs an item at `target` on release
+    pub lob: bool,
 }
 
 impl PlayerAim {
@@ -89,6 +133,8 @@ impl PlayerAim {
         Self {
             dir: Dir2::Y,
             max_rotation_speed: 5.0,
+            target: Vec3::ZERO,
+            lob: false,
         }
     }
     pub fn direction3(&self) -> Dir3 {
@@ -130,7 +176,7 @@ fn update_aim(
     >,
     camera: CameraParams,
 ) {
-    for (aim, player, gtr, action_state) in &mut players {
+    for (mut aim, player, gtr, action_state) in &mut players {
         match player.controller {
             GameController::KeyBoard => {
                 let Some(ray) = camera.mouse_ray() else {
@@ -147,6 +193,9 @@ fn update_aim(
                 };
                 let target = ray.origin + ray.direction * dist;
                 gizmos.sphere(target, Quat::default(), 0.1, Color::BLACK);
+                if aim.target != target {
+                    aim.target = target;
+                }
                 let Ok(direction) = Dir2::new(target.xz() - player_pos.xz()) else {
                     log::error!(
                         "Failed to normalize direction between camera ray and player {}",
@@ -164,7 +213,13 @@ fn update_aim(
                 else {
                     continue;
                 };
-                let direction = Dir2::new(dir * Vec2::new(1.0, -1.0)).unwrap_or(Dir2::Y);
+                let dir = dir * Vec2::new(1.0, -1.0);
+                let target = gtr.translation().xz() + dir * LOB_MAX_RANGE;
+                let target = Vec3::new(target.x, 0.0, target.y);
+                if aim.target != target {
+                    aim.target = target;
+                }
+                let direction = Dir2::new(dir).unwrap_or(Dir2::Y);
                 let mut dir = aim.map_unchanged(|aim| &mut aim.dir);
                 dir.set_if_neq(direction);
             }
@@ -179,10 +234,11 @@ fn update_skills(
         &mut ActiveSkill,
         &ActionState<PlayerInput>,
         Has<Dead>,
+        Has<HitStun>,
     )>,
 ) {
     let dt = time.delta_seconds();
-    for (mut state, mut active, input, dead) in &mut players {
+    for (mut state, mut active, input, dead, stunned) in &mut players {
         state
             .cooldowns
             .values_mut()
@@ -192,6 +248,15 @@ fn update_skills(
             active.active = None;
             continue;
         }
+        // Stuns cancel the current skill without triggering it
+        if stunned {
+            state.charge = 0.0;
+            state.lobbing = false;
+            if active.active.is_some() {
+                active.active = None;
+            }
+            continue;
+        }
         if let Some(skill) = active.active {
             if !input.pressed(&PlayerInput::Skill(skill)) {
                 active.active = None;
@@ -233,16 +298,129 @@ fn collector_skills(
 }
 
 fn throw_skill(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut players: Query<(
+        &Player,
+        &Children,
+        &ActiveSkill,
+        &PlayerAim,
+        &mut SkillState,
+        Has<Dead>,
+    )>,
+    collectors: Query<&Collector>,
+) {
+    let dt = time.delta_seconds();
+    for (player, children, active, aim, mut state, dead) in &mut players {
+        if aim.lob {
+            state.charge = 0.0;
+            continue;
+        }
+        if active.active == Some(PlayerSkill::Shoot) {
+            // A tap still registers as a charge on a zero delta frame
+            state.charge = (state.charge + dt).clamp(f32::EPSILON, CHARGED_THROW_DURATION);
+            continue;
+        }
+        // The skill was released
+        if state.charge <= 0.0 {
+            continue;
+        }
+        let ratio = state.charge / CHARGED_THROW_DURATION;
+        state.charge = 0.0;
+        if dead {
+            continue;
+        }
+        // Damage scales with the throw speed through impact damage
+        let force = THROW_FORCE.lerp(CHARGED_THROW_MAX_FORCE, ratio);
+        for collector in collectors.iter_many(children) {
+            if let Some(command) = collector.throw_collected(aim.direction2(), force) {
+                commands.add(command);
+            } else {
+                log::info!("Player {}, Nothing to shoot", player.id);
+            }
+        }
+    }
+}
+
+/// Flight time of a lobbed throw from `from` to `target`
+fn lob_flight_time(from: Vec3, target: Vec3) -> f32 {
+    (from.xz().distance(target.xz()) / LOB_SPEED).max(LOB_MIN_FLIGHT_TIME)
+}
+
+fn lob_throw_skill(
+    mut commands: Commands,
+    mut gizmos: Gizmos,
+    gravity: Res<Gravity>,
+    mut players: Query<(
+        &Player,
+        &Children,
+        &ActiveSkill,
+        &PlayerAim,
+        &mut SkillState,
+        Has<Dead>,
+    )>,
+    collectors: Query<(&Collector, &CollectorConfig, &GlobalTransform)>,
+) {
+    for (player, children, active, aim, mut state, dead) in &mut players {
+        if !aim.lob {
+            state.lobbing = false;
+            continue;
+        }
+        // Preview the trajectory while aiming
+        if active.active == Some(PlayerSkill::Shoot) {
+            state.lobbing = true;
+            for (collector, config, gtr) in collectors.iter_many(children) {
+                if collector.is_empty() {
+                    continue;
+                }
+                let from = gtr.translation() + Vec3::Y * 0.5;
+                let flight_time = lob_flight_time(from, aim.target);
+                let damping = GarbageItem::LINEAR_DAMPING;
+                let velocity =
+                    ballistic_velocity(from, aim.target, flight_time, gravity.0, damping);
+                let points = (0..=LOB_PREVIEW_STEPS).map(|i| {
+                    let time = flight_time * i as f32 / LOB_PREVIEW_STEPS as f32;
+                    ballistic_position(from, velocity, time, gravity.0, damping)
+                });
+                gizmos.linestrip(points, config.color);
+                gizmos.circle(aim.target, Dir3::Y, 0.5, config.color);
+            }
+            continue;
+        }
+        // The skill was released
+        if !std::mem::take(&mut state.lobbing) || dead {
+            continue;
+        }
+        for (collector, _, gtr) in collectors.iter_many(children) {
+            let flight_time = lob_flight_time(gtr.translation(), aim.target);
+            if let Some(command) = collector.throw_lobbed(aim.target, aim.direction2(), flight_time)
+            {
+                commands.add(command);
+            } else {
+                log::info!("Player {}, Nothing to shoot", player.id);
+            }
+        }
+    }
+}
+
+fn salvo_skill(
     mut commands: Commands,
     players: Query<(&Player, &Children, &ActiveSkill, &PlayerAim), Changed<ActiveSkill>>,
     collectors: Query<&Collector>,
 ) {
+    let step = SALVO_SPREAD / (SALVO_COUNT - 1) as f32;
     for (player, children, active, aim) in &players {
-        if active.active != Some(PlayerSkill::Shoot) {
+        if active.active != Some(PlayerSkill::Salvo) {
             continue;
         }
+        let directions: Vec<_> = (0..SALVO_COUNT)
+            .map(|i| {
+                let angle = (i as f32).mul_add(step, -SALVO_SPREAD / 2.0);
+                Dir2::new_unchecked(Vec2::from_angle(angle).rotate(*aim.direction2()))
+            })
+            .collect();
         for collector in collectors.iter_many(children) {
-            if let Some(command) = collector.throw_collected(aim.direction2(), 70.0) {
+            if let Some(command) = collector.throw_salvo(&directions, SALVO_FORCE) {
                 commands.add(command);
             } else {
                 log::info!("Player {}, Nothing to shoot", player.id);
@@ -251,22 +429,61 @@ fn throw_skill(
     }
 }
 
+fn cycle_ammo(
+    players: Query<(&Children, &ActionState<PlayerInput>), With<Player>>,
+    mut collectors: Query<&mut Collector>,
+    items: Query<&GarbageItem>,
+) {
+    for (children, state) in &players {
+        if !state.just_pressed(&PlayerInput::CycleAmmo) {
+            continue;
+        }
+        let mut collectors = collectors.iter_many_mut(children);
+        while let Some(mut collector) = collectors.fetch_next() {
+            let mut kinds: Vec<_> = items.iter_many(collector.collected()).copied().collect();
+            kinds.sort_unstable();
+            kinds.dedup();
+            let policy = collector.policy().next(&kinds);
+            collector.set_policy(policy);
+        }
+    }
+}
+
+fn toggle_lob(mut players: Query<(&mut PlayerAim, &ActionState<PlayerInput>), With<Player>>) {
+    for (mut aim, state) in &mut players {
+        if state.just_pressed(&PlayerInput::ToggleLob) {
+            aim.lob = !aim.lob;
+        }
+    }
+}
+
 fn dash_skill(
     mut commands: Commands,
-    players: Query<(Entity, &PlayerAim, &ActiveSkill, &LinearVelocity), Changed<ActiveSkill>>,
+    players: Query<
+        (
+            Entity,
+            &PlayerAim,
+            &ActiveSkill,
+            &LinearVelocity,
+            &CarriedMass,
+        ),
+        Changed<ActiveSkill>,
+    >,
 ) {
     const DASH_SPEED: f32 = 500.0;
 
-    for (entity, aim, skill, linvel) in &players {
+    for (entity, aim, skill, linvel, carried) in &players {
         if skill.active != Some(PlayerSkill::Dash) {
             continue;
         }
-        let direction = (linvel.length_squared() > 1.0)
-            .then(|| Vec3::new(linvel.x, 0.0, linvel.z).normalize())
-            .unwrap_or(*aim.direction3());
-        commands
-            .entity(entity)
-            .insert(ExternalImpulse::new(direction * DASH_SPEED));
+        let direction = if linvel.length_squared() > 1.0 {
+            Vec3::new(linvel.x, 0.0, linvel.z).normalize()
+        } else {
+            *aim.direction3()
+        };
+        commands.entity(entity).insert(ExternalImpulse::new(
+            direction * DASH_SPEED * carried.speed_factor(),
+        ));
     }
 }
 
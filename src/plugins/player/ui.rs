@@ -1,7 +1,13 @@
 use std::f32::consts::FRAC_PI_6;
 
 use super::{assets::PlayerAssets, Player, PlayerInput};
-use crate::{plugins::ui::input_icons::InputMapIcons, GameState, Health};
+use crate::{
+    plugins::{
+        garbage::{Collector, GarbageAssets, ThrowPolicy},
+        ui::input_icons::InputMapIcons,
+    },
+    GameState, Health,
+};
 use bevy::{prelude::*, utils::HashMap};
 
 pub struct PlayerUiPlugin;
@@ -10,10 +16,16 @@ impl Plugin for PlayerUiPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<UiState>()
             .register_type::<HealthUi>()
+            .register_type::<AmmoUi>()
             .add_systems(Startup, setup_ui)
             .add_systems(
                 PostUpdate,
-                (create_player_ui, update_health, update_input_icons),
+                (
+                    create_player_ui,
+                    update_health,
+                    update_ammo,
+                    update_input_icons,
+                ),
             )
             .add_systems(OnEnter(GameState::Pause), toggle_controls)
             .add_systems(OnExit(GameState::Pause), toggle_controls);
@@ -47,6 +59,11 @@ fn toggle_controls(
 // Player -> Ui
 struct HealthUi(Entity);
 
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+// Player -> Ui
+struct AmmoUi(Entity);
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 // Player -> Ui
@@ -127,6 +144,37 @@ fn update_health(
     }
 }
 
+fn ammo_label(policy: ThrowPolicy, assets: Option<&GarbageAssets>) -> String {
+    match policy {
+        ThrowPolicy::ClosestToAim => "Any".to_owned(),
+        ThrowPolicy::Heaviest => "Heaviest".to_owned(),
+        ThrowPolicy::Lightest => "Lightest".to_owned(),
+        ThrowPolicy::Kind(item) => assets
+            .and_then(|a| a.catalog.get(item))
+            .map_or_else(|| "Unknown".to_owned(), |def| def.name.clone()),
+    }
+}
+
+fn update_ammo(
+    players: Query<(&Children, &AmmoUi)>,
+    collectors: Query<&Collector, Changed<Collector>>,
+    assets: Option<Res<GarbageAssets>>,
+    mut ui: Query<&mut Text>,
+) {
+    for (children, AmmoUi(ui_entity)) in &players {
+        let Some(collector) = collectors.iter_many(children).next() else {
+            continue;
+        };
+        let Ok(mut text) = ui.get_mut(*ui_entity) else {
+            continue;
+        };
+        let label = ammo_label(collector.policy(), assets.as_deref());
+        if text.sections[0].value != label {
+            text.sections[0].value = label;
+        }
+    }
+}
+
 fn create_player_ui(
     mut commands: Commands,
     new_players: Query<(Entity, &Player, &InputMapIcons), Added<Player>>,
@@ -182,6 +230,34 @@ fn create_player_ui(
                 Name::new("Player text"),
             ))
             .set_parent(root);
+        let ammo_ui = commands
+            .spawn((
+                TextBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        bottom: Val::Px(55.0),
+                        left: Val::Px(0.0),
+                        ..default()
+                    },
+                    text: Text {
+                        sections: vec![TextSection {
+                            value: ammo_label(ThrowPolicy::default(), None),
+                            style: TextStyle {
+                                font_size: 15.0,
+                                color,
+                                ..default()
+                            },
+                        }],
+                        justify: JustifyText::Left,
+                        ..default()
+                    },
+                    ..default()
+                },
+                Name::new("Ammo text"),
+            ))
+            .set_parent(root)
+            .id();
+        commands.entity(entity).insert(AmmoUi(ammo_ui));
         let health_root = commands
             .spawn((
                 NodeBundle {